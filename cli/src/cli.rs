@@ -1,7 +1,7 @@
 use clap::{Args, CommandFactory, Parser, Subcommand, ValueHint};
 use clap_complete::{generate, Shell};
 use color_eyre::eyre::Result;
-use std::io;
+use std::{io, path::PathBuf};
 
 /// DB Hub - A CLI tool for managing multi-environment database connections
 #[derive(Parser)]
@@ -29,6 +29,70 @@ pub enum Commands {
     /// Manage database connection contexts
     #[command(alias = "e")]
     Context(ContextArgs),
+    /// Manage configuration files
+    #[command(alias = "cfg")]
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Manage the Lua scripts that build the connection commands
+    Script {
+        #[command(subcommand)]
+        command: ScriptCommands,
+    },
+    /// Import connections from other clients' config files
+    Import {
+        /// Format of the file: mycnf, pgpass, pg-service, mongo, redis, dbeaver or datagrip
+        #[arg()]
+        format: dbhub_core::import::ImportFormat,
+        /// File to import from, e.g. ~/.my.cnf, ~/.pgpass or DBeaver's data-sources.json
+        #[arg(value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+        /// Environment of the imported connections
+        #[arg(long, default_value = "local")]
+        env: String,
+        /// Config file to write to, defaults to ~/.dbhub/imported.yml
+        #[arg(long, short, value_hint = ValueHint::FilePath)]
+        output: Option<PathBuf>,
+        /// Only show what would be imported
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Export connections to other tools' formats
+    Export {
+        /// Target format: pgpass, mycnf, dbeaver, env or docker-compose
+        #[arg(long, short)]
+        format: dbhub_core::export::ExportFormat,
+        /// Only export matching connections, e.g. env=local, db_type=mysql or alias=my-db
+        #[arg(long, value_name = "KEY=VALUE")]
+        filter: Vec<String>,
+        /// Output file, defaults to stdout
+        #[arg(long, short, value_hint = ValueHint::FilePath)]
+        output: Option<PathBuf>,
+    },
+    /// Print environment variable exports of a connection, e.g. `eval $(dbhub env my-db)`
+    Env {
+        /// Connection alias
+        #[arg(value_hint = ValueHint::Other, required = true)]
+        alias: String,
+        /// Output syntax: bash, zsh, fish or dotenv
+        #[arg(long, default_value = "bash")]
+        shell: dbhub_core::export::Shell,
+    },
+    /// Run a command with a connection's environment variables, e.g. `dbhub run my-db -- ./migrate up`
+    ///
+    /// The variables are set as configured, secrets are not resolved and no SSH tunnel is opened.
+    /// Exits with the command's exit code, or 128 + signal when the command was killed by a signal.
+    Run {
+        /// Connection alias
+        #[arg(value_hint = ValueHint::Other, required = true)]
+        alias: String,
+
+        /// Command to run and its arguments
+        #[arg(trailing_var_arg = true, required = true)]
+        #[arg(allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
     /// Generate shell completion scripts
     #[command(alias = "comp")]
     Completion {
@@ -45,6 +109,98 @@ pub enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Migrate config files to the current format version
+    Migrate {
+        /// Config files to migrate, defaults to all loaded config files
+        #[arg(value_hint = ValueHint::FilePath)]
+        files: Vec<PathBuf>,
+        /// Only report which files need migration
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Convert a config file between yaml, toml and json
+    Convert {
+        /// Config file to convert
+        #[arg(value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+        /// Target format: yaml, toml or json
+        #[arg(long)]
+        to: dbhub_core::ConfigFormat,
+        /// Output file, defaults to the input file with the new extension
+        #[arg(long, short, value_hint = ValueHint::FilePath)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ScriptCommands {
+    /// List the embedded scripts and the scripts in the script directories with their status
+    #[command(alias = "ls")]
+    List,
+    /// Show the changes of the script file used for a database type against the embedded one
+    Diff {
+        /// Database type of the script, e.g. mysql
+        db_type: String,
+    },
+    /// Go back to the embedded script, changed scripts in the script directories are kept as .bak
+    Reset {
+        /// Database type of the script, e.g. mysql
+        db_type: String,
+    },
+    /// Create a script in ~/.dbhub overriding the embedded one
+    Override {
+        /// Database type of the script, e.g. mysql
+        db_type: String,
+        /// Write a script calling dbhub.base() instead of a full copy, so fixes to the embedded script still apply
+        #[arg(long)]
+        base: bool,
+    },
+    /// Run a script once with the given variables and print its output, no command is executed
+    Test {
+        /// Database type of the script, e.g. mysql
+        db_type: String,
+        /// Template variables, e.g. --vars host=localhost --vars port=3306
+        #[arg(long, value_name = "KEY=VALUE", value_parser = parse_key_value)]
+        vars: Vec<(String, String)>,
+        /// Annotations, e.g. --annotations postgres/client=pgcli
+        #[arg(long, value_name = "KEY=VALUE", value_parser = parse_key_value)]
+        annotations: Vec<(String, String)>,
+        /// Output lines of the previous step, e.g. --last-output 10.0.0.1 --last-output 6379
+        #[arg(long, value_name = "LINE")]
+        last_output: Vec<String>,
+        /// Stderr lines of the previous step
+        #[arg(long, value_name = "LINE")]
+        last_stderr: Vec<String>,
+        /// Exit code of the previous step
+        #[arg(long, value_name = "CODE", allow_hyphen_values = true)]
+        last_exit_code: Option<i32>,
+        /// dbhub.state left by the previous step, e.g. --state '{"tried": ["primary"]}'
+        #[arg(long, value_name = "JSON", value_parser = parse_json)]
+        state: Option<serde_json::Value>,
+        /// Step number, defaults to 1 with --last-output, --last-stderr or --last-exit-code and 0 otherwise
+        #[arg(long)]
+        count: Option<usize>,
+        /// Runtime args passed to the script
+        #[arg(trailing_var_arg = true)]
+        #[arg(allow_hyphen_values = true)]
+        runtime_args: Vec<String>,
+    },
+}
+
+/// Parse a JSON argument.
+fn parse_json(s: &str) -> std::result::Result<serde_json::Value, String> {
+    serde_json::from_str(s).map_err(|e| format!("invalid JSON: {e}"))
+}
+
+/// Parse a `KEY=VALUE` argument.
+fn parse_key_value(s: &str) -> std::result::Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("invalid `{s}`, expected KEY=VALUE"))
+}
+
 #[derive(Args)]
 pub struct ContextArgs {
     /// Generate default config file
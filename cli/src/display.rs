@@ -1,3 +1,4 @@
+use color_eyre::eyre::{eyre, Result};
 use console::{style, StyledObject};
 use dbhub_core::{Config, Database};
 use std::collections::BTreeMap;
@@ -36,6 +37,33 @@ impl Filter {
             alias: args.filter_alias.clone(),
         }
     }
+
+    /// Build a filter from `key=value` pairs, e.g. `env=local`.
+    pub fn from_pairs(pairs: &[String]) -> Result<Self> {
+        let mut filter = Filter::default();
+        for pair in pairs {
+            let (key, value) = pair.split_once('=')
+                .ok_or_else(|| eyre!("Invalid filter `{}`, expected KEY=VALUE", pair))?;
+            let value = Some(value.to_string());
+            match key {
+                "env" => filter.env = value,
+                "db_type" | "type" => filter.db_type = value,
+                "alias" => filter.alias = value,
+                _ => return Err(eyre!("Unknown filter key `{}`, expected env, db_type or alias", key)),
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Check whether a database passes the filter.
+    pub fn matches(&self, db: &Database) -> bool {
+        let matches = |expected: &Option<String>, actual: &str| {
+            expected.as_ref().is_none_or(|expected| expected == actual)
+        };
+        matches(&self.env, &db.env)
+            && matches(&self.db_type, &db.db_type)
+            && matches(&self.alias, &db.alias)
+    }
 }
 
 /// Options for listing connections.
@@ -64,25 +92,8 @@ pub fn list_connections(config: &Config, opts: &ListOptions) {
     let mut grouped_databases: BTreeMap<&str, BTreeMap<&str, Vec<&Database>>> = BTreeMap::new();
 
     for (i, db) in config.databases.iter().enumerate() {
-        // Apply environment filter
-        if let Some(ref specified_env) = opts.filter.env {
-            if db.env != *specified_env {
-                continue;
-            }
-        }
-
-        // Apply alias filter
-        if let Some(ref specified_alias) = opts.filter.alias {
-            if db.alias != *specified_alias {
-                continue;
-            }
-        }
-
-        // Apply db_type filter
-        if let Some(ref specified_db_type) = opts.filter.db_type {
-            if db.db_type != *specified_db_type {
-                continue;
-            }
+        if !opts.filter.matches(db) {
+            continue;
         }
 
         found_databases += 1;
@@ -95,7 +106,7 @@ pub fn list_connections(config: &Config, opts: &ListOptions) {
             .push(config.databases.get(i).unwrap());
     }
 
-    print_databases(grouped_databases, opts);
+    print_databases(config, grouped_databases, opts);
 
     if found_databases == 0 {
         println!("{}", style("No databases found.").red());
@@ -103,6 +114,7 @@ pub fn list_connections(config: &Config, opts: &ListOptions) {
 }
 
 fn print_databases(
+    config: &Config,
     grouped_databases: BTreeMap<&str, BTreeMap<&str, Vec<&Database>>>,
     opts: &ListOptions,
 ) {
@@ -134,7 +146,8 @@ fn print_databases(
                 }
 
                 if opts.format.with_dsn {
-                    let dsn = format!("🔗 DSN : {}", style(&db.dsn).dim());
+                    let dsn = config.dsn(db).unwrap_or_else(|e| e.to_string());
+                    let dsn = format!("🔗 DSN : {}", style(dsn).dim());
                     println!("\t{dsn}");
                 }
 
@@ -153,3 +166,40 @@ fn print_databases(
         }
     }
 }
+
+/// Print the output of a Lua script run by `dbhub script test`.
+pub fn print_lua_output(output: &dbhub_core::connection::LuaOutput) {
+    println!("{} {}", style("command_with_args:").bold(), output.command_with_args);
+    println!("{} {}", style("again:").bold(), output.again);
+    if !output.env.is_empty() {
+        println!("{}", style("env:").bold());
+        let env: BTreeMap<_, _> = output.env.iter().collect();
+        for (key, value) in env {
+            println!("\t{key}={value}");
+        }
+    }
+    if let Some(stdin) = &output.stdin {
+        println!("{}", style("stdin:").bold());
+        for line in stdin.lines() {
+            println!("\t{line}");
+        }
+    }
+    if let Some(init_file) = &output.init_file {
+        println!("{}", style("init_file:").bold());
+        for line in init_file.lines() {
+            println!("\t{line}");
+        }
+    }
+    if let Some(max_iterations) = output.max_iterations {
+        println!("{} {}", style("max_iterations:").bold(), max_iterations);
+    }
+    if let Some(timeout_ms) = output.timeout_ms {
+        println!("{} {}", style("timeout_ms:").bold(), timeout_ms);
+    }
+    if output.allow_failure {
+        println!("{} {}", style("allow_failure:").bold(), output.allow_failure);
+    }
+    if !output.state.is_null() && output.state != serde_json::json!({}) {
+        println!("{} {}", style("state:").bold(), output.state);
+    }
+}
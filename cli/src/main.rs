@@ -6,7 +6,7 @@ mod cli;
 mod display;
 mod r#match;
 
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, ConfigCommands, ScriptCommands};
 use display::ListOptions;
 use r#match::find_similar_alias;
 
@@ -72,6 +72,37 @@ fn main() -> Result<()> {
             let opts = ListOptions::from_args(&args);
             display::list_connections(&cfg, &opts);
         }
+        Commands::Config { command } => {
+            handle_config(command)?;
+        }
+        Commands::Script { command } => {
+            handle_script(command)?;
+        }
+        Commands::Import { format, file, env, output, dry_run } => {
+            let cfg = dbhub_core::loads()?;
+            let options = dbhub_core::import::ImportOptions { env };
+            let imported = dbhub_core::import::import_file(format, &file, &cfg, &options)?;
+            handle_import(imported, output, dry_run)?;
+        }
+        Commands::Export { format, filter, output } => {
+            let cfg = dbhub_core::loads()?;
+            let filter = display::Filter::from_pairs(&filter)?;
+            handle_export(&cfg, format, &filter, output)?;
+        }
+        Commands::Env { ref alias, shell } => {
+            let cfg = dbhub_core::loads()?;
+            let db = find_database(&cfg, alias)?;
+            let env_vars = cfg.env_vars(db)?;
+            print!("{}", dbhub_core::export::render_env_exports(&env_vars, shell));
+        }
+        Commands::Run { ref alias, ref command } => {
+            let cfg = dbhub_core::loads()?;
+            let db = find_database(&cfg, alias)?;
+            let code = dbhub_core::run(db, &cfg, command)?;
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
         Commands::Completion { shell } => {
             cli::handle_completion(shell)?;
         }
@@ -89,6 +120,94 @@ fn handle_connect(
     alias: &str,
     passthrough_args: &[String],
 ) -> Result<()> {
+    let db = find_database(cfg, alias)?;
+
+    tracing::debug!("passthrough_args: {:?}", passthrough_args);
+
+    dbhub_core::connect(db, cfg, passthrough_args)
+}
+
+fn handle_config(command: ConfigCommands) -> Result<()> {
+    match command {
+        ConfigCommands::Migrate { files, dry_run } => {
+            let files = if files.is_empty() { dbhub_core::get_config_paths() } else { files };
+            for file in &files {
+                let from = dbhub_core::config::migrate_config_file(file, dry_run)?;
+                let target = dbhub_core::config::CONFIG_VERSION;
+                if from == target {
+                    println!("✓ {} is up to date (version {target})", file.display());
+                } else if dry_run {
+                    println!("• {} needs migration: version {from} -> {target}", file.display());
+                } else {
+                    println!("✓ {} migrated: version {from} -> {target}, backup saved as .bak", file.display());
+                }
+            }
+        }
+        ConfigCommands::Convert { file, to, output } => {
+            let converted = dbhub_core::config::convert_config_file(&file, to, output.as_deref())?;
+            println!(
+                "✓ {} converted to {}, original saved as .bak",
+                file.display(),
+                converted.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn handle_script(command: ScriptCommands) -> Result<()> {
+    // A broken config should not keep the scripts from being listed or tested.
+    let cfg = dbhub_core::loads().ok();
+    match command {
+        ScriptCommands::List => {
+            for script in dbhub_core::connection::list_scripts(cfg.as_ref())? {
+                println!("{:<16} {:<11} {}", script.db_type, script.status, script.path.display());
+            }
+        }
+        ScriptCommands::Diff { db_type } => {
+            match dbhub_core::connection::diff_script(&db_type, cfg.as_ref())? {
+                Some(diff) => print!("{diff}"),
+                None => println!("✓ {db_type}.lua is the same as the embedded script"),
+            }
+        }
+        ScriptCommands::Reset { db_type } => {
+            let backups = dbhub_core::connection::reset_script(&db_type, cfg.as_ref())?;
+            if backups.is_empty() {
+                println!("✓ {db_type}.lua reset");
+            }
+            for backup in backups {
+                println!("✓ {db_type}.lua reset, the old script was saved as {}", backup.display());
+            }
+        }
+        ScriptCommands::Override { db_type, base } => {
+            let path = dbhub_core::connection::override_script(&db_type, base)?;
+            println!("✓ Created {}, it overrides the embedded {db_type}.lua", path.display());
+        }
+        ScriptCommands::Test {
+            db_type, vars, annotations, last_output, last_stderr, last_exit_code, state, count, runtime_args,
+        } => {
+            let later_step = !last_output.is_empty() || !last_stderr.is_empty() || last_exit_code.is_some();
+            let context = dbhub_core::connection::LuaContext {
+                count: count.unwrap_or(if later_step { 1 } else { 0 }),
+                last_output_lines: last_output,
+                last_exit_code,
+                last_stderr_lines: last_stderr,
+                state: state.unwrap_or_default(),
+                ..dbhub_core::connection::LuaContext::new(
+                    vars.into_iter().collect(),
+                    annotations.into_iter().collect(),
+                    runtime_args,
+                )
+            };
+            let output = dbhub_core::connection::test_script(&db_type, &context, cfg.as_ref())?;
+            display::print_lua_output(&output);
+        }
+    }
+    Ok(())
+}
+
+/// Find a database by alias, suggesting a similar alias if not found.
+fn find_database<'a>(cfg: &'a dbhub_core::Config, alias: &str) -> Result<&'a dbhub_core::Database> {
     use color_eyre::eyre::eyre;
 
     let db_index = cfg.aliases.get(alias).ok_or_else(|| {
@@ -96,11 +215,66 @@ fn handle_connect(
         eyre!("Alias '{}' not found, maybe {}?", alias, similar_alias)
     })?;
 
-    let db = cfg.get_database_by_index(*db_index).unwrap();
+    Ok(cfg.get_database_by_index(*db_index).unwrap())
+}
+
+fn handle_import(
+    imported: dbhub_core::import::Imported,
+    output: Option<std::path::PathBuf>,
+    dry_run: bool,
+) -> Result<()> {
+    use color_eyre::eyre::eyre;
 
-    tracing::debug!("passthrough_args: {:?}", passthrough_args);
+    for db in &imported.databases {
+        println!("✓ {} ({}, {})", db.alias, db.db_type, db.env);
+    }
+    for warning in &imported.warnings {
+        println!("! {warning}");
+    }
+    for alias in &imported.conflicts {
+        println!("✗ {alias}: alias already exists, skipped");
+    }
 
-    dbhub_core::connect(db, cfg, passthrough_args)
+    if imported.databases.is_empty() {
+        println!("No connections to import.");
+        return Ok(());
+    }
+    if dry_run {
+        return Ok(());
+    }
+
+    let output = match output {
+        Some(output) => output,
+        None => dbhub_core::get_config_dir()
+            .ok_or_else(|| eyre!("Cannot determine config directory"))?
+            .join("imported.yml"),
+    };
+    dbhub_core::config::add_databases(&output, &imported.databases)?;
+    println!("Imported {} connection(s) into {}", imported.databases.len(), output.display());
+    Ok(())
+}
+
+fn handle_export(
+    cfg: &dbhub_core::Config,
+    format: dbhub_core::export::ExportFormat,
+    filter: &display::Filter,
+    output: Option<std::path::PathBuf>,
+) -> Result<()> {
+    let databases: Vec<_> = cfg.databases.iter().filter(|db| filter.matches(db)).collect();
+    let exported = dbhub_core::export::export(cfg, &databases, format)?;
+
+    for skipped in &exported.skipped {
+        eprintln!("! {skipped}");
+    }
+
+    match output {
+        Some(output) => {
+            std::fs::write(&output, &exported.content)?;
+            eprintln!("Exported to {}", output.display());
+        }
+        None => print!("{}", exported.content),
+    }
+    Ok(())
 }
 
 fn handle_completion_suggestions(cfg: &dbhub_core::Config, suggestion_type: &str) -> Result<()> {
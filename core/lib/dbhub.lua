@@ -0,0 +1,54 @@
+-- dbhub.lua, the prelude shared by scripts: `local prelude = require("dbhub")`.
+-- Using LUA 5.4
+--
+-- Modules are looked up in the `lib/` directory of each script directory (e.g. `./.dbhub/lib/`
+-- and `~/.dbhub/lib/`) before the modules embedded in dbhub, `require("a.b")` loads `a/b.lua`.
+
+local M = {}
+
+-- Whether s is set and not empty.
+function M.non_empty(s)
+    return s ~= nil and s ~= ""
+end
+
+-- Whether s is "true", "1" or "yes".
+function M.is_true(s)
+    return s == "true" or s == "1" or s == "yes"
+end
+
+-- A lookup of connection options, the `<prefix>/<name>` annotation wins over the `name` query parameter.
+-- e.g. `local option = prelude.options("redis")` and then `option("tls")`.
+function M.options(prefix)
+    local query = dbhub.parse_query(dbhub.variables.query)
+    return function(name)
+        local value = dbhub.annotations[prefix .. "/" .. name]
+        if M.non_empty(value) then
+            return value
+        end
+        return query[name]
+    end
+end
+
+-- The runtime args as shell quoted words, each with a leading space, to append to a command.
+function M.runtime_args()
+    local args = ""
+    for _, arg in ipairs(dbhub.runtime_args) do
+        args = args .. " " .. dbhub.shell_quote(arg)
+    end
+    return args
+end
+
+-- "h1:7000,h2:7001" -> { { host = "h1", port = "7000" }, { host = "h2", port = "7001" } }
+function M.parse_nodes(list, default_port)
+    local nodes = {}
+    for node in string.gmatch(list or "", "[^,%s]+") do
+        local host, port = node:match("^%[?([^%]]+)%]?:(%d+)$")
+        if host == nil then
+            host, port = node, default_port
+        end
+        table.insert(nodes, { host = host, port = port })
+    end
+    return nodes
+end
+
+return M
@@ -0,0 +1,70 @@
+-- $HOME/.dbhub/cassandra.lua
+-- Using LUA 5.4
+--
+-- Works for Apache Cassandra and ScyllaDB.
+--
+-- Annotations:
+--   cassandra/client: the cqlsh binary to use, `cqlsh` by default.
+--   cassandra/ssl: "true" to connect with `--ssl`, `ssl=true` in the dsn query works as well.
+--   cassandra/certfile: the CA certificate used to validate the server, set as SSL_CERTFILE.
+--   cassandra/validate: "false" to skip certificate validation, set as SSL_VALIDATE.
+--
+-- cqlsh reads an interactive password from the terminal only, so the password is passed with `-p`.
+-- Runtime args are appended to the client command, e.g. `dbhub c my-cassandra -- -e "describe tables"`.
+
+assert(dbhub ~= nil, "dbhub is not defined")
+assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
+assert(dbhub.annotations ~= nil, "dbhub.annotations is not defined")
+assert(dbhub.runtime_args ~= nil, "dbhub.runtime_args is not defined")
+
+local variables = dbhub.variables
+local annotations = dbhub.annotations
+
+local client_key = "cassandra/client"
+
+local shell_quote = dbhub.shell_quote
+local prelude = require("dbhub")
+local non_empty, is_true = prelude.non_empty, prelude.is_true
+
+-- query parameters are case insensitive, e.g. TrustServerCertificate=true
+local options = {}
+for key, value in pairs(dbhub.parse_query(variables.query)) do
+    options[string.lower(key)] = value
+end
+
+local client = annotations[client_key]
+if not non_empty(client) then
+    client = "cqlsh"
+end
+
+local port = variables.port
+if not non_empty(port) then
+    port = "9042"
+end
+
+local args = string.format("%s %s %s", client, shell_quote(variables.host or ""), shell_quote(port))
+if non_empty(variables.user) then
+    args = args .. " -u " .. shell_quote(variables.user)
+    args = args .. " -p " .. shell_quote(variables.password or "")
+end
+if non_empty(variables.keyspace) then
+    args = args .. " -k " .. shell_quote(variables.keyspace)
+end
+
+local env = {}
+if is_true(annotations["cassandra/ssl"]) or is_true(options.ssl) then
+    args = args .. " --ssl"
+    if non_empty(annotations["cassandra/certfile"]) then
+        env.SSL_CERTFILE = annotations["cassandra/certfile"]
+    end
+    if non_empty(annotations["cassandra/validate"]) then
+        env.SSL_VALIDATE = annotations["cassandra/validate"]
+    end
+end
+args = args .. prelude.runtime_args()
+
+return {
+    command_with_args = args,
+    again = false,
+    env = env
+}
@@ -0,0 +1,76 @@
+-- $HOME/.dbhub/clickhouse.lua
+-- Using LUA 5.4
+--
+-- Annotations:
+--   clickhouse/client: the client command, `clickhouse-client` (default) or `clickhouse client`.
+--   clickhouse/secure: "true" to connect with TLS (`--secure`), `secure=true` in the dsn query works as well.
+--   clickhouse/setting/<name>: a session setting passed as `--<name>=<value>`,
+--     e.g. `clickhouse/setting/max_threads: "8"`.
+--
+-- The password is passed through the CLICKHOUSE_PASSWORD environment variable instead of the command line.
+-- The port defaults to 9000, or 9440 for secure connections.
+-- Runtime args are appended to the client command, e.g. `dbhub c my-ch -- --query "select 1"`.
+
+assert(dbhub ~= nil, "dbhub is not defined")
+assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
+assert(dbhub.annotations ~= nil, "dbhub.annotations is not defined")
+assert(dbhub.runtime_args ~= nil, "dbhub.runtime_args is not defined")
+
+local variables = dbhub.variables
+local annotations = dbhub.annotations
+
+local client_key = "clickhouse/client"
+local secure_key = "clickhouse/secure"
+local setting_prefix = "clickhouse/setting/"
+
+local shell_quote = dbhub.shell_quote
+local prelude = require("dbhub")
+local non_empty, is_true = prelude.non_empty, prelude.is_true
+
+local client = annotations[client_key]
+if not non_empty(client) then
+    client = "clickhouse-client"
+end
+
+local secure = is_true(annotations[secure_key]) or is_true(dbhub.parse_query(variables.query).secure)
+
+local port = variables.port
+if not non_empty(port) then
+    port = secure and "9440" or "9000"
+end
+
+local args = string.format("%s --host %s --port %s", client, shell_quote(variables.host or ""), shell_quote(port))
+if non_empty(variables.user) then
+    args = args .. " --user " .. shell_quote(variables.user)
+end
+if non_empty(variables.database) then
+    args = args .. " --database " .. shell_quote(variables.database)
+end
+if secure then
+    args = args .. " --secure"
+end
+
+-- sort the settings so the command line is stable
+local settings = {}
+for key, value in pairs(annotations) do
+    if key:sub(1, #setting_prefix) == setting_prefix then
+        table.insert(settings, { key:sub(#setting_prefix + 1), value })
+    end
+end
+table.sort(settings, function(a, b) return a[1] < b[1] end)
+for _, setting in ipairs(settings) do
+    args = args .. " " .. shell_quote(string.format("--%s=%s", setting[1], setting[2]))
+end
+
+args = args .. prelude.runtime_args()
+
+local env = {}
+if non_empty(variables.password) then
+    env.CLICKHOUSE_PASSWORD = variables.password
+end
+
+return {
+    command_with_args = args,
+    again = false,
+    env = env
+}
@@ -0,0 +1,68 @@
+-- $HOME/.dbhub/doris.lua
+-- Using LUA 5.4
+--
+-- Apache Doris and StarRocks speak the MySQL protocol, connect to the FE host with the query port (9030 by default).
+--
+-- Annotations:
+--   doris/client: the client to use, `mysql` (default) or `mysqlsh`.
+--
+-- The password is passed through the MYSQL_PWD environment variable instead of the command line.
+-- Runtime args are appended to the client command, e.g. `dbhub c my-doris -- -e "show backends"`.
+
+assert(dbhub ~= nil, "dbhub is not defined")
+assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
+assert(dbhub.annotations ~= nil, "dbhub.annotations is not defined")
+assert(dbhub.runtime_args ~= nil, "dbhub.runtime_args is not defined")
+
+local variables = dbhub.variables
+local annotations = dbhub.annotations
+
+local client_key = "doris/client"
+local default_query_port = "9030"
+
+local shell_quote = dbhub.shell_quote
+local prelude = require("dbhub")
+local non_empty = prelude.non_empty
+
+local client = annotations[client_key]
+if not non_empty(client) then
+    client = "mysql"
+end
+
+local port = variables.port
+if not non_empty(port) then
+    port = default_query_port
+end
+
+local args
+if client == "mysqlsh" then
+    args = string.format("mysqlsh --sql -h %s -P %s", shell_quote(variables.host or ""), shell_quote(port))
+else
+    args = string.format("%s -h %s -P %s", client, shell_quote(variables.host or ""), shell_quote(port))
+end
+
+if non_empty(variables.user) then
+    args = args .. " -u " .. shell_quote(variables.user)
+end
+
+-- `catalog.database` is accepted by both Doris and StarRocks for multi-catalog setups.
+if non_empty(variables.database) then
+    if client == "mysqlsh" then
+        args = args .. " --database=" .. shell_quote(variables.database)
+    else
+        args = args .. " -D " .. shell_quote(variables.database)
+    end
+end
+
+args = args .. prelude.runtime_args()
+
+local env = {}
+if non_empty(variables.password) then
+    env.MYSQL_PWD = variables.password
+end
+
+return {
+    command_with_args = args,
+    again = false,
+    env = env
+}
@@ -0,0 +1,64 @@
+-- $HOME/.dbhub/etcd.lua
+-- Using LUA 5.4
+--
+-- The `endpoints` variable is a comma separated list, e.g. `etcd://root:pass@e1:2379,e2:2379?`.
+-- Endpoints without a scheme use https when a certificate is configured, http otherwise.
+--
+-- Annotations:
+--   etcd/cacert, etcd/cert, etcd/key: certificate files passed to etcdctl.
+--   etcd/insecure: "true" to skip certificate verification.
+--
+-- The user and password are passed through ETCDCTL_USER and ETCDCTL_PASSWORD instead of the command line.
+-- Runtime args are the etcdctl command, `endpoint status -w table` by default,
+-- e.g. `dbhub c my-etcd -- get --prefix /registry/ --keys-only`.
+
+assert(dbhub ~= nil, "dbhub is not defined")
+assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
+assert(dbhub.annotations ~= nil, "dbhub.annotations is not defined")
+assert(dbhub.runtime_args ~= nil, "dbhub.runtime_args is not defined")
+
+local variables = dbhub.variables
+local annotations = dbhub.annotations
+
+local shell_quote = dbhub.shell_quote
+local prelude = require("dbhub")
+local non_empty, is_true = prelude.non_empty, prelude.is_true
+
+local tls = false
+local cert_args = ""
+for _, name in ipairs({ "cacert", "cert", "key" }) do
+    local value = annotations["etcd/" .. name]
+    if non_empty(value) then
+        tls = true
+        cert_args = cert_args .. " --" .. name .. " " .. shell_quote(value)
+    end
+end
+if is_true(annotations["etcd/insecure"]) then
+    cert_args = cert_args .. " --insecure-skip-tls-verify"
+end
+
+local endpoints = {}
+for endpoint in string.gmatch(variables.endpoints or "", "[^,%s]+") do
+    if not endpoint:find("://") then
+        endpoint = (tls and "https://" or "http://") .. endpoint
+    end
+    table.insert(endpoints, endpoint)
+end
+
+local args = "etcdctl --endpoints " .. shell_quote(table.concat(endpoints, ",")) .. cert_args
+if #dbhub.runtime_args == 0 then
+    args = args .. " endpoint status -w table"
+end
+args = args .. prelude.runtime_args()
+
+local env = { ETCDCTL_API = "3" }
+if non_empty(variables.user) then
+    env.ETCDCTL_USER = variables.user
+    env.ETCDCTL_PASSWORD = variables.password or ""
+end
+
+return {
+    command_with_args = args,
+    again = false,
+    env = env
+}
@@ -0,0 +1,131 @@
+-- $HOME/.dbhub/kafka.lua
+-- Using LUA 5.4
+--
+-- The `brokers` variable is a comma separated bootstrap list, e.g. `kafka://user:pass@b1:9092,b2:9092?`.
+-- Query parameters of the dsn are passed as client properties, e.g. `?security.protocol=SASL_SSL`.
+--
+-- Annotations:
+--   kafka/client: `kcat` (default), `kafka-console-consumer` or `kafka-console-producer`,
+--     the `.sh` suffix of the Apache Kafka distribution is accepted too.
+--   kafka/topic: the topic to consume from or produce to. kcat lists the cluster metadata when it's empty.
+--   kafka/security-protocol: e.g. `SASL_SSL`, defaults to `SASL_SSL` when a user is given.
+--   kafka/sasl-mechanism: e.g. `PLAIN`, `SCRAM-SHA-256` or `SCRAM-SHA-512`, defaults to `PLAIN`.
+--   kafka/property/<name>: any other client property, e.g. `kafka/property/ssl.ca.location: /etc/ssl/ca.pem`.
+--
+-- Runtime args are appended to the client command, e.g. `dbhub c my-kafka -- -C -o -10 -e`.
+
+assert(dbhub ~= nil, "dbhub is not defined")
+assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
+assert(dbhub.annotations ~= nil, "dbhub.annotations is not defined")
+assert(dbhub.runtime_args ~= nil, "dbhub.runtime_args is not defined")
+
+local variables = dbhub.variables
+local annotations = dbhub.annotations
+
+local client_key = "kafka/client"
+local topic_key = "kafka/topic"
+local security_protocol_key = "kafka/security-protocol"
+local sasl_mechanism_key = "kafka/sasl-mechanism"
+local property_prefix = "kafka/property/"
+
+local shell_quote = dbhub.shell_quote
+
+local function non_empty(s)
+    return s ~= nil and s ~= ""
+end
+
+-- properties are sorted by name so the command line is stable
+local properties = {}
+local function set_property(name, value)
+    for _, property in ipairs(properties) do
+        if property[1] == name then
+            property[2] = value
+            return
+        end
+    end
+    table.insert(properties, { name, value })
+end
+
+-- the query order is kept, dbhub.parse_query returns an unordered table
+for pair in string.gmatch(variables.query or "", "[^&]+") do
+    local name, value = pair:match("^([^=]+)=(.*)$")
+    if name ~= nil then
+        set_property(name, dbhub.url_decode(value))
+    end
+end
+
+if non_empty(variables.user) then
+    local mechanism = annotations[sasl_mechanism_key]
+    if not non_empty(mechanism) then
+        mechanism = "PLAIN"
+    end
+    set_property("security.protocol", "SASL_SSL")
+    set_property("sasl.mechanism", mechanism)
+end
+if non_empty(annotations[security_protocol_key]) then
+    set_property("security.protocol", annotations[security_protocol_key])
+end
+
+local annotated = {}
+for key, value in pairs(annotations) do
+    if key:sub(1, #property_prefix) == property_prefix then
+        table.insert(annotated, { key:sub(#property_prefix + 1), value })
+    end
+end
+table.sort(annotated, function(a, b) return a[1] < b[1] end)
+for _, property in ipairs(annotated) do
+    set_property(property[1], property[2])
+end
+
+local client = annotations[client_key]
+if not non_empty(client) then
+    client = "kcat"
+end
+local brokers = variables.brokers or ""
+local topic = annotations[topic_key]
+
+local args
+if client == "kcat" or client == "kafkacat" then
+    args = string.format("%s -b %s", client, shell_quote(brokers))
+    for _, property in ipairs(properties) do
+        args = args .. " -X " .. shell_quote(property[1] .. "=" .. property[2])
+    end
+    if non_empty(variables.user) then
+        args = args .. " -X " .. shell_quote("sasl.username=" .. variables.user)
+        args = args .. " -X " .. shell_quote("sasl.password=" .. (variables.password or ""))
+    end
+    if non_empty(topic) then
+        args = args .. " -t " .. shell_quote(topic)
+    elseif #dbhub.runtime_args == 0 then
+        args = args .. " -L"
+    end
+else
+    -- kafka-console-consumer(.sh) and kafka-console-producer(.sh)
+    local property_flag = client:find("producer") and "--producer-property" or "--consumer-property"
+    args = string.format("%s --bootstrap-server %s", client, shell_quote(brokers))
+    if non_empty(topic) then
+        args = args .. " --topic " .. shell_quote(topic)
+    end
+    for _, property in ipairs(properties) do
+        args = args .. " " .. property_flag .. " " .. shell_quote(property[1] .. "=" .. property[2])
+    end
+    if non_empty(variables.user) then
+        local module = "org.apache.kafka.common.security.plain.PlainLoginModule"
+        local mechanism = annotations[sasl_mechanism_key] or ""
+        if mechanism:find("^SCRAM") then
+            module = "org.apache.kafka.common.security.scram.ScramLoginModule"
+        end
+        local jaas = string.format('%s required username="%s" password="%s";', module, variables.user,
+            variables.password or "")
+        args = args .. " " .. property_flag .. " " .. shell_quote("sasl.jaas.config=" .. jaas)
+    end
+end
+
+for _, arg in ipairs(dbhub.runtime_args) do
+    args = args .. " " .. shell_quote(arg)
+end
+
+return {
+    command_with_args = args,
+    again = false
+}
@@ -1,4 +1,4 @@
--- $HOME/.dbhub/sample.lua
+-- $HOME/.dbhub/memcached.lua
 -- Using LUA 5.4
 
 assert(dbhub ~= nil, "dbhub is not defined")
@@ -1,5 +1,12 @@
--- $HOME/.dbhub/mysql.lua
+-- $HOME/.dbhub/mongo.lua
 -- Using LUA 5.4
+--
+-- Annotations:
+--   mongo/srv: "true" to connect with a `mongodb+srv://` URI, the port is not used.
+--   mongo/hosts: a comma separated `host:port` list, e.g. of a replica set, used instead of host and port.
+--
+-- Characters of the user and password that are special in URIs are percent-encoded,
+-- values that are already encoded (e.g. `p%40ss`) are kept as they are.
 
 assert(dbhub ~= nil, "dbhub is not defined")
 assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
@@ -25,8 +32,41 @@ if #dbhub.runtime_args > 0 then
     end
 end
 
+-- percent-encode URI delimiters, keeping existing `%XX` escapes
+local function encode_userinfo(s)
+    local encoded = {}
+    local i = 1
+    while i <= #s do
+        local c = s:sub(i, i)
+        if c == "%" and s:sub(i + 1, i + 2):match("^%x%x$") then
+            table.insert(encoded, s:sub(i, i + 2))
+            i = i + 3
+        else
+            if c:match("[%w%-%._~!$&'()*+,;=]") then
+                table.insert(encoded, c)
+            else
+                table.insert(encoded, string.format("%%%02X", c:byte()))
+            end
+            i = i + 1
+        end
+    end
+    return table.concat(encoded)
+end
+
+local annotations = dbhub.annotations or {}
+local scheme = "mongodb"
+local hosts = string.format("%s:%s", variables.host, variables.port)
+if annotations["mongo/srv"] == "true" then
+    scheme = "mongodb+srv"
+    hosts = variables.host
+elseif annotations["mongo/hosts"] and annotations["mongo/hosts"] ~= "" then
+    hosts = annotations["mongo/hosts"]
+end
+
 -- mongodb://{user}:{password}@{host}:{port}/{database}?{query}
-local args = string.format("mongosh mongodb://%s:%s@%s:%s/%s?%s", variables.user, variables.password, variables.host, variables.port, db, variables.query)
+local uri = string.format("%s://%s:%s@%s/%s?%s", scheme, encode_userinfo(variables.user or ""),
+    encode_userinfo(variables.password or ""), hosts, db, variables.query or "")
+local args = "mongosh " .. dbhub.shell_quote(uri)
 
 return {
     command_with_args = args,
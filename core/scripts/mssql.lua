@@ -0,0 +1,66 @@
+-- $HOME/.dbhub/mssql.lua
+-- Using LUA 5.4
+--
+-- Annotations:
+--   mssql/client: the sqlcmd binary to use, `sqlcmd` by default (both go-sqlcmd and the ODBC sqlcmd work).
+--   mssql/trust-server-certificate: "true" to pass `-C`, `TrustServerCertificate=true` in the dsn query works as well.
+--   mssql/encrypt: "true" to pass `-N`, `encrypt=true` in the dsn query works as well.
+--
+-- The password is passed through the SQLCMDPASSWORD environment variable, sqlcmd reads it for `-P`.
+-- Runtime args are appended to the client command, e.g. `dbhub c my-mssql -- -Q "select @@version"`.
+
+assert(dbhub ~= nil, "dbhub is not defined")
+assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
+assert(dbhub.annotations ~= nil, "dbhub.annotations is not defined")
+assert(dbhub.runtime_args ~= nil, "dbhub.runtime_args is not defined")
+
+local variables = dbhub.variables
+local annotations = dbhub.annotations
+
+local client_key = "mssql/client"
+
+local shell_quote = dbhub.shell_quote
+local prelude = require("dbhub")
+local non_empty, is_true = prelude.non_empty, prelude.is_true
+
+-- query parameters are case insensitive, e.g. TrustServerCertificate=true
+local options = {}
+for key, value in pairs(dbhub.parse_query(variables.query)) do
+    options[string.lower(key)] = value
+end
+
+local client = annotations[client_key]
+if not non_empty(client) then
+    client = "sqlcmd"
+end
+
+local port = variables.port
+if not non_empty(port) then
+    port = "1433"
+end
+
+local args = string.format("%s -S %s", client, shell_quote(string.format("tcp:%s,%s", variables.host or "", port)))
+if non_empty(variables.user) then
+    args = args .. " -U " .. shell_quote(variables.user)
+end
+if non_empty(variables.database) then
+    args = args .. " -d " .. shell_quote(variables.database)
+end
+if is_true(annotations["mssql/trust-server-certificate"]) or is_true(options.trustservercertificate) then
+    args = args .. " -C"
+end
+if is_true(annotations["mssql/encrypt"]) or is_true(options.encrypt) then
+    args = args .. " -N"
+end
+args = args .. prelude.runtime_args()
+
+local env = {}
+if non_empty(variables.password) then
+    env.SQLCMDPASSWORD = variables.password
+end
+
+return {
+    command_with_args = args,
+    again = false,
+    env = env
+}
@@ -1,5 +1,9 @@
 -- $HOME/.dbhub/mysql.lua
 -- Using LUA 5.4
+--
+-- Annotations, e.g. imported from ~/.my.cnf:
+--   mysql/socket: the unix socket file, used when the host is localhost.
+--   mysql/ssl-*: TLS options passed as `--ssl-*`, e.g. mysql/ssl-ca, mysql/ssl-cert, mysql/ssl-key, mysql/ssl-mode.
 
 assert(dbhub ~= nil, "dbhub is not defined")
 assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
@@ -21,6 +25,23 @@ end
 local args = string.format("mysqlsh -h %s -P %d -u %s %s %s",
              variables.host, variables.port, variables.user, password, optional_database)
 
+local annotations = dbhub.annotations or {}
+if annotations["mysql/socket"] and annotations["mysql/socket"] ~= "" then
+    args = args .. " --socket=" .. dbhub.shell_quote(annotations["mysql/socket"])
+end
+-- sorted, so the command is the same on every run
+local ssl_options = {}
+for key, value in pairs(annotations) do
+    local name = key:match("^mysql/(ssl%-[%w%-]+)$")
+    if name ~= nil and value ~= "" then
+        table.insert(ssl_options, "--" .. name .. "=" .. dbhub.shell_quote(value))
+    end
+end
+table.sort(ssl_options)
+if #ssl_options > 0 then
+    args = args .. " " .. table.concat(ssl_options, " ")
+end
+
 return {
     command_with_args = args,
     again = false
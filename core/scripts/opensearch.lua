@@ -0,0 +1,73 @@
+-- $HOME/.dbhub/opensearch.lua
+-- Using LUA 5.4
+--
+-- A curl based REST shell for OpenSearch. The runtime args are the method and the path,
+-- followed by any extra curl options, e.g.
+--   dbhub c my-opensearch                                  # GET /
+--   dbhub c my-opensearch -- GET '/_cat/indices?v'
+--   dbhub c my-opensearch -- POST /logs/_search -d '{"size": 1}'
+--
+-- Annotations:
+--   opensearch/scheme: `http` (default) or `https`, `scheme=https` in the dsn query works as well.
+--   opensearch/api-key: sent as `Authorization: ApiKey <key>` instead of the user and password.
+--   opensearch/cacert: the CA certificate passed to curl with `--cacert`.
+--   opensearch/insecure: "true" to skip certificate verification.
+
+assert(dbhub ~= nil, "dbhub is not defined")
+assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
+assert(dbhub.annotations ~= nil, "dbhub.annotations is not defined")
+assert(dbhub.runtime_args ~= nil, "dbhub.runtime_args is not defined")
+
+local variables = dbhub.variables
+local annotations = dbhub.annotations
+
+local shell_quote = dbhub.shell_quote
+local prelude = require("dbhub")
+local non_empty, is_true = prelude.non_empty, prelude.is_true
+
+local option = prelude.options("opensearch")
+
+local scheme = option("scheme")
+if not non_empty(scheme) then
+    scheme = "http"
+end
+local port = variables.port
+if not non_empty(port) then
+    port = "9200"
+end
+
+local method = "GET"
+local path = "/"
+local extra = {}
+for i, arg in ipairs(dbhub.runtime_args) do
+    if i == 1 and arg:match("^%u+$") then
+        method = arg
+    elseif path == "/" and #extra == 0 and arg:sub(1, 1) == "/" then
+        path = arg
+    else
+        table.insert(extra, arg)
+    end
+end
+
+local args = "curl -sS -X " .. method .. " -H " .. shell_quote("Content-Type: application/json")
+if non_empty(option("api-key")) then
+    args = args .. " -H " .. shell_quote("Authorization: ApiKey " .. option("api-key"))
+elseif non_empty(variables.user) then
+    args = args .. " -u " .. shell_quote(variables.user .. ":" .. (variables.password or ""))
+end
+if non_empty(option("cacert")) then
+    args = args .. " --cacert " .. shell_quote(option("cacert"))
+end
+if is_true(option("insecure")) then
+    args = args .. " -k"
+end
+
+args = args .. " " .. shell_quote(string.format("%s://%s:%s%s", scheme, variables.host or "", port, path))
+for _, arg in ipairs(extra) do
+    args = args .. " " .. shell_quote(arg)
+end
+
+return {
+    command_with_args = args,
+    again = false
+}
@@ -0,0 +1,56 @@
+-- $HOME/.dbhub/oracle.lua
+-- Using LUA 5.4
+--
+-- Annotations:
+--   oracle/client: `sqlplus` (default) or `sql` for SQLcl.
+--   oracle/role: a privileged role to connect with, e.g. `sysdba`.
+--
+-- The client starts with `/nolog @file`, where the file is a private temporary file holding the
+-- `connect user/"pw"@//host:port/service` logon, so the password never shows up on the command line
+-- and the client keeps the terminal.
+-- Runtime args are appended to the client command, e.g. `dbhub c my-oracle -- -S`.
+
+assert(dbhub ~= nil, "dbhub is not defined")
+assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
+assert(dbhub.annotations ~= nil, "dbhub.annotations is not defined")
+assert(dbhub.runtime_args ~= nil, "dbhub.runtime_args is not defined")
+
+local variables = dbhub.variables
+local annotations = dbhub.annotations
+
+local client_key = "oracle/client"
+local role_key = "oracle/role"
+
+local prelude = require("dbhub")
+local non_empty = prelude.non_empty
+
+-- query parameters are case insensitive, e.g. TrustServerCertificate=true
+local options = {}
+for key, value in pairs(dbhub.parse_query(variables.query)) do
+    options[string.lower(key)] = value
+end
+
+local client = annotations[client_key]
+if not non_empty(client) then
+    client = "sqlplus"
+end
+
+local port = variables.port
+if not non_empty(port) then
+    port = "1521"
+end
+
+local args = client .. prelude.runtime_args() .. " /nolog @{init_file}"
+
+-- EZConnect: //host:port/service
+local logon = string.format('%s/"%s"@//%s:%s/%s', variables.user or "", variables.password or "",
+    variables.host or "", port, variables.service or "")
+if non_empty(annotations[role_key]) then
+    logon = logon .. " as " .. annotations[role_key]
+end
+
+return {
+    command_with_args = args,
+    again = false,
+    init_file = "connect " .. logon .. "\n"
+}
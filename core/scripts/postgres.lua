@@ -0,0 +1,79 @@
+-- $HOME/.dbhub/postgres.lua
+-- Using LUA 5.4
+--
+-- Annotations:
+--   postgres/client: the client to use, `psql` (default) or `pgcli`.
+--
+-- The password is passed through the PGPASSWORD environment variable instead of the command line,
+-- when it is empty the client falls back to ~/.pgpass.
+-- Query options of the dsn (e.g. `sslmode=require&connect_timeout=5`) are passed as connection parameters.
+-- Runtime args are appended to the client command, e.g. `dbhub c my-pg -- -c "select 1"`.
+
+assert(dbhub ~= nil, "dbhub is not defined")
+assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
+assert(dbhub.annotations ~= nil, "dbhub.annotations is not defined")
+assert(dbhub.runtime_args ~= nil, "dbhub.runtime_args is not defined")
+
+local variables = dbhub.variables
+local annotations = dbhub.annotations
+
+local client_key = "postgres/client"
+
+local shell_quote = dbhub.shell_quote
+local prelude = require("dbhub")
+local non_empty = prelude.non_empty
+
+-- conninfo values with spaces or quotes must be quoted: key='it\'s'
+local function conninfo_value(s)
+    if s ~= "" and not s:find("[%s'\\]") then
+        return s
+    end
+    return "'" .. (s:gsub("\\", "\\\\"):gsub("'", "\\'")) .. "'"
+end
+
+local client = annotations[client_key]
+if not non_empty(client) then
+    client = "psql"
+end
+
+local args
+if client == "pgcli" then
+    -- pgcli takes a URI, the password still comes from PGPASSWORD.
+    local uri = string.format("postgresql://%s@%s:%s/%s", variables.user or "", variables.host or "",
+        variables.port or "5432", variables.database or "")
+    if non_empty(variables.query) then
+        uri = uri .. "?" .. variables.query
+    end
+    args = string.format("pgcli %s", shell_quote(uri))
+else
+    local params = {}
+    for _, key in ipairs({ "host", "port", "user" }) do
+        if non_empty(variables[key]) then
+            table.insert(params, key .. "=" .. conninfo_value(variables[key]))
+        end
+    end
+    if non_empty(variables.database) then
+        table.insert(params, "dbname=" .. conninfo_value(variables.database))
+    end
+    -- sslmode=require&connect_timeout=5 -> sslmode=require connect_timeout=5
+    for pair in string.gmatch(variables.query or "", "[^&]+") do
+        local key, value = pair:match("^([^=]+)=(.*)$")
+        if key ~= nil then
+            table.insert(params, key .. "=" .. conninfo_value(dbhub.url_decode(value)))
+        end
+    end
+    args = string.format("%s %s", client, shell_quote(table.concat(params, " ")))
+end
+
+args = args .. prelude.runtime_args()
+
+local env = {}
+if non_empty(variables.password) then
+    env.PGPASSWORD = variables.password
+end
+
+return {
+    command_with_args = args,
+    again = false,
+    env = env
+}
@@ -0,0 +1,43 @@
+-- $HOME/.dbhub/redis-cluster.lua
+-- Using LUA 5.4
+--
+-- The `nodes` variable is a comma separated seed list, e.g. `redis-cluster://:pass@n1:7000,n2:7001?`.
+-- redis-cli connects to a random seed with `-c` and follows the cluster redirections from there.
+--
+-- TLS and ACL options, set as annotations or as query parameters of the dsn (without the `redis/` prefix):
+--   redis/tls: "true" to connect with `--tls`.
+--   redis/cacert, redis/cert, redis/key: certificate files passed to redis-cli.
+--   redis/sni: the server name for SNI.
+--   redis/insecure: "true" to skip certificate verification.
+--   redis/acl: "true" to pass the `user` variable as the ACL username with `--user`.
+-- The password is passed through REDISCLI_AUTH, the user is ignored without `redis/acl`.
+-- Runtime args are appended to redis-cli, e.g. `dbhub c my-cluster -- cluster info`.
+
+assert(dbhub ~= nil, "dbhub is not defined")
+assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
+assert(dbhub.count ~= nil, "dbhub.count is not defined")
+assert(dbhub.last_output_lines ~= nil, "dbhub.last_output_lines is not defined")
+assert(dbhub.annotations ~= nil, "dbhub.annotations is not defined")
+assert(dbhub.runtime_args ~= nil, "dbhub.runtime_args is not defined")
+
+local variables = dbhub.variables
+
+local shell_quote = dbhub.shell_quote
+local prelude = require("dbhub")
+local redis = require("dbhub.redis")
+
+local nodes = prelude.parse_nodes(variables.nodes, "6379")
+if #nodes == 0 and prelude.non_empty(variables.host) then
+    nodes = { { host = variables.host, port = variables.port or "6379" } }
+end
+assert(#nodes > 0, "redis-cluster needs at least one seed node in the `nodes` variable")
+
+local seed = nodes[math.random(#nodes)]
+local args = string.format("redis-cli -c -h %s -p %s", shell_quote(seed.host), shell_quote(seed.port))
+args = args .. redis.connection_options(variables.user) .. prelude.runtime_args()
+
+return {
+    command_with_args = args,
+    again = false,
+    env = redis.password_env(variables.password)
+}
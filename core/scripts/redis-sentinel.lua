@@ -1,68 +1,106 @@
--- $HOME/.dbhub/mysql.lua
+-- $HOME/.dbhub/redis-sentinel.lua
 -- Using LUA 5.4
+--
+-- The `sentinels` variable is a comma separated list of sentinels,
+-- e.g. `redis-sentinel://:pass@s1:26379,s2:26379,s3:26379/0?`.
+-- Templates with `{host1}:{port1}`, `{host2}:{port2}`, ... variables are still supported.
+--
+-- Annotations:
+--   redis-sentinel/mastername: the master name, `mymaster` by default.
+--   redis-sentinel/password: the password of the sentinels when it differs from the master.
+--
+-- TLS and ACL options, set as annotations or as query parameters of the dsn (without the `redis/` prefix):
+--   redis/tls: "true" to connect with `--tls`.
+--   redis/cacert, redis/cert, redis/key: certificate files passed to redis-cli.
+--   redis/sni: the server name for SNI.
+--   redis/insecure: "true" to skip certificate verification.
+--   redis/acl: "true" to pass the `user` variable as the ACL username with `--user`.
+-- The password is passed through REDISCLI_AUTH, the user is ignored without `redis/acl`.
+-- The TLS options apply to both the sentinel and the master connection.
+--
+-- Without TLS the sentinels are asked directly with `dbhub.tcp_request`, in random order until one
+-- answers, so the master is found without running redis-cli first. In a sandbox this needs the
+-- `network` capability, otherwise (or when all sentinels fail) a redis-cli step asks a random sentinel.
 
 assert(dbhub ~= nil, "dbhub is not defined")
 assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
 assert(dbhub.count ~= nil, "dbhub.count is not defined")
 assert(dbhub.last_output_lines ~= nil, "dbhub.last_output_lines is not defined")
-
 assert(dbhub.annotations ~= nil, "dbhub.annotations is not defined")
+assert(dbhub.runtime_args ~= nil, "dbhub.runtime_args is not defined")
 
 local variables = dbhub.variables
 local annotations = dbhub.annotations
 
-local master_name_key = "redis-sentinel/mastername"
+local shell_quote = dbhub.shell_quote
+local prelude = require("dbhub")
+local redis = require("dbhub.redis")
+
+local sentinel_password_key = "redis-sentinel/password"
 
--- step1: get master from sentinel, support 3 sentinels at most.
+-- step1: get master from a random sentinel.
 local function get_master()
-    local hosts = {}
-    for i = 1, 3 do
-        local host = variables["host" .. i]
-        local port = variables["port" .. i]
-        if host and host ~= "" and port and port ~= "" then
-            table.insert(hosts, { host = host, port = port })
-        end
-    end
+    local nodes = redis.sentinels(variables)
+    assert(#nodes > 0, "redis-sentinel needs at least one sentinel in the `sentinels` variable")
+    local selected = nodes[math.random(#nodes)]
 
-    local selected = #hosts > 0 and hosts[math.random(#hosts)] or { host = "", port = "" }
-    -- Get the master name from the `annotations.master_name` variable.
-    -- If it's not set, use the default value "mymaster".
-    local master_name = "mymaster"
-    if annotations[master_name_key] and annotations[master_name_key] ~= "" then
-        master_name = annotations[master_name_key]
+    local args = string.format("redis-cli -h %s -p %s", shell_quote(selected.host), shell_quote(selected.port))
+    args = args .. redis.connection_options(nil)
+    args = args .. " sentinel get-master-addr-by-name " .. shell_quote(redis.master_name(annotations))
+    return args, redis.password_env(annotations[sentinel_password_key])
+end
+
+-- step1 without redis-cli: the master from the first sentinel answering, nil when none does.
+local function lookup_master()
+    if dbhub.tcp_request == nil or prelude.is_true(prelude.options("redis")("tls")) then
+        return nil
+    end
+    local nodes = redis.sentinels(variables)
+    for i = #nodes, 2, -1 do
+        local j = math.random(i)
+        nodes[i], nodes[j] = nodes[j], nodes[i]
+    end
+    for _, node in ipairs(nodes) do
+        local ok, host, port = pcall(redis.sentinel_master, node, redis.master_name(annotations),
+            annotations[sentinel_password_key], 1000)
+        if ok then
+            return host, port
+        end
+        dbhub.log("debug", "sentinel lookup failed: " .. tostring(host))
     end
-    
-    return string.format("redis-cli -h %s -p %s sentinel get-master-addr-by-name %s", selected.host, selected.port, master_name)
+    return nil
 end
 
 -- step2: generate redis-cli command line
-local function command()
-    --print("debugging last_output_lines: ")
-    --for i, line in ipairs(dbhub.last_output_lines) do
-    --     print(string.format("  Line %d: %s", i, line))
-    --end
+local function command(host, port)
+    local args = string.format("redis-cli -h %s -p %s", shell_quote(host), shell_quote(port))
+    args = args .. redis.connection_options(variables.user)
+    if prelude.non_empty(variables.database) then
+        args = args .. " -n " .. shell_quote(variables.database)
+    end
+    return args .. prelude.runtime_args(), redis.password_env(variables.password)
+end
 
+local again = false
+local args, env
+if dbhub.count < 1 then
+    local host, port = lookup_master()
+    if host ~= nil then
+        args, env = command(host, port)
+    else
+        again = true
+        args, env = get_master()
+    end
+else
     -- We expect the last output to be the master address like this:
     -- [1] = "127.0.0.1"
     -- [2] = "6379"
-
     assert(#dbhub.last_output_lines == 2, "Expected 2 lines of output from sentinel get-master-addr-by-name")
-    local host = dbhub.last_output_lines[1]
-    local port = dbhub.last_output_lines[2]
-
-    local database = ""
-    if variables.database and variables.database ~= "" then
-        database = string.format("-n %s", variables.database)
-    end
-
-    return string.format("redis-cli -h %s -p %s %s", host, port, database)
+    args, env = command(dbhub.last_output_lines[1], dbhub.last_output_lines[2])
 end
 
--- again MUST be number so that it can be used in the return statement.
-local again = dbhub.count < 1
-local args = again and get_master() or command()
-
 return {
     command_with_args = args,
-    again = again
+    again = again,
+    env = env
 }
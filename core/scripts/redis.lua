@@ -1,26 +1,37 @@
--- $HOME/.dbhub/mysql.lua
+-- $HOME/.dbhub/redis.lua
 -- Using LUA 5.4
+--
+-- TLS and ACL options, set as annotations or as query parameters of the dsn (without the `redis/` prefix):
+--   redis/tls: "true" to connect with `--tls`.
+--   redis/cacert, redis/cert, redis/key: certificate files passed to redis-cli.
+--   redis/sni: the server name for SNI.
+--   redis/insecure: "true" to skip certificate verification.
+--   redis/acl: "true" to pass the `user` variable as the ACL username with `--user`.
+-- The password is passed through REDISCLI_AUTH, the user is ignored without `redis/acl`.
+-- Runtime args are appended to redis-cli, e.g. `dbhub c my-redis -- info memory`.
 
 assert(dbhub ~= nil, "dbhub is not defined")
 assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
 assert(dbhub.count ~= nil, "dbhub.count is not defined")
 assert(dbhub.last_output_lines ~= nil, "dbhub.last_output_lines is not defined")
+assert(dbhub.annotations ~= nil, "dbhub.annotations is not defined")
+assert(dbhub.runtime_args ~= nil, "dbhub.runtime_args is not defined")
 
 local variables = dbhub.variables
 
-local optional_password = ""
-if variables.password and variables.password ~= "" then
-    optional_password = string.format("-a %s", variables.password)
-end
+local shell_quote = dbhub.shell_quote
+local prelude = require("dbhub")
+local redis = require("dbhub.redis")
 
-local database = ""
-if variables.database and variables.database ~= "" then
-    database = string.format("-n %s", variables.database)
+local args = string.format("redis-cli -h %s -p %s", shell_quote(variables.host or ""), shell_quote(variables.port or "6379"))
+args = args .. redis.connection_options(variables.user)
+if prelude.non_empty(variables.database) then
+    args = args .. " -n " .. shell_quote(variables.database)
 end
-
-local args = string.format("redis-cli -h %s -p %d %s %s", variables.host, variables.port, optional_password, database)
+args = args .. prelude.runtime_args()
 
 return {
     command_with_args = args,
-    again = false
+    again = false,
+    env = redis.password_env(variables.password)
 }
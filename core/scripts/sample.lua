@@ -13,6 +13,52 @@
 --                  It is a table of hashmap.
 --   - runtime_args: a list of command line arguments passed from the command line.
 --                  It is a table of array of strings.
+--   - last_exit_code: the exit code of the previous command, nil in the first step or when it was killed.
+--   - last_stderr_lines: the stderr lines of the previous command, a table of array of strings.
+--   - state: a table kept across the steps, e.g. `dbhub.state.tried = "primary"`.
+--            It holds strings, numbers, booleans and tables with either string keys or array indexes.
+--
+-- and the following helper functions:
+--   - shell_quote(s): quotes s as a single shell word, e.g. `it's` -> `'it'\''s'`.
+--   - url_encode(s), url_decode(s): percent-encoding.
+--   - parse_query(query): turns `a=1&b=2` into { a = "1", b = "2" }, values are url decoded.
+--   - expand_home(path): expands `~` and `~/...` to the home directory.
+--   - getenv(name): the value of an environment variable, or nil. Needs `os` in the sandbox.
+--   - which(program): the full path of a program found in PATH, or nil. Needs `os` in the sandbox.
+--   - file_exists(path): whether the file exists, `~` is expanded. Needs `io` in the sandbox.
+--   - json_decode(s): decodes a JSON document into Lua tables and values.
+--   - log(level, message): logs through dbhub, level is one of trace, debug, info, warn, error.
+--   - base(): in a ~/.dbhub/<db_type>.lua overriding an embedded script, runs the embedded script
+--             and returns its result, e.g. `local output = dbhub.base()`.
+--
+-- The network helpers find endpoints without running a client in an extra step. They raise an error on
+-- failure (use pcall) and take an optional table with timeout_ms (3000 by default) and max_bytes (1 MiB).
+-- In the sandbox they need the `network` capability, otherwise they are nil.
+--   - tcp_request(host, port, data, opts): sends data and returns the response, read until the server
+--             closes the connection or `opts.complete(response)` returns true.
+--   - dns_srv(name, opts): the SRV records { target, port, priority, weight } sorted by priority,
+--             e.g. `dbhub.dns_srv("_mongodb._tcp.cluster0.example.com")`. `opts.server` sets the nameserver.
+--   - http_get(url, opts): { status, headers, body } of a plain http url, `opts.headers` adds headers,
+--             e.g. `dbhub.json_decode(dbhub.http_get("http://127.0.0.1:8500/v1/catalog/service/db").body)`.
+--
+-- `require` loads shared modules from `lib/` of the script directories (e.g. ~/.dbhub/lib/ and
+-- ./.dbhub/lib/), then from the modules embedded in dbhub, also in the sandbox. `require("a.b")` loads `a/b.lua`.
+-- The embedded `dbhub` prelude has non_empty, is_true, options(prefix), runtime_args and parse_nodes,
+-- and `dbhub.redis` has the redis-cli TLS options and sentinel discovery, e.g. `local prelude = require("dbhub")`.
+--
+-- The script returns a table with:
+--   - command_with_args: the command to run.
+--   - again: whether to run the command, capture its output and run the script again.
+--   - env: (optional) a table of environment variables for the command, e.g. { PGPASSWORD = "..." }.
+--   - stdin: (optional) text written to the command's standard input, which is then closed,
+--            so the session is not interactive.
+--   - init_file: (optional) text written to a temporary file only readable by you, `{init_file}` in
+--                command_with_args is replaced with its path, e.g. `sqlplus /nolog @{init_file}`.
+--                The file is removed when the command exits.
+--   - max_iterations: (optional) the maximum number of steps, 5 by default and at most 50.
+--   - timeout_ms: (optional) kills the command run for `again` after this many milliseconds.
+--   - allow_failure: (optional) runs the script again even when the command run for `again` fails,
+--                    e.g. to try a replica when the primary is down.
 
 assert(dbhub ~= nil, "dbhub is not defined")
 assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
@@ -25,10 +71,15 @@ print("dbhub #?", dbhub)
 
 local variables = dbhub.variables
 
-local args = string.format("sample-cli -h %s -P %d -u %s -p%s --database=%s",
-    variables.host, variables.port, variables.user, variables.password, variables.database)
+local options = dbhub.parse_query(variables.query)
+dbhub.log("debug", "sample tls option: " .. (options.tls or "unset"))
+
+local args = string.format("sample-cli -h %s -P %d -u %s --database=%s",
+    dbhub.shell_quote(variables.host), variables.port, dbhub.shell_quote(variables.user),
+    dbhub.shell_quote(variables.database))
 
 return {
-    command_with_args = args, -- the command to run
-    again = false             -- indicates whether to run the script again
+    command_with_args = args,                -- the command to run
+    again = false,                           -- indicates whether to run the script again
+    env = { SAMPLE_PWD = variables.password } -- keeps the password off the command line
 }
@@ -0,0 +1,69 @@
+-- $HOME/.dbhub/sqlite.lua
+-- Using LUA 5.4
+--
+-- The dsn is a file path: `sqlite:///abs/path.db`, `sqlite://~/data/app.db` or `sqlite://data/app.db`,
+-- `~` is expanded to the home directory and relative paths to the current directory.
+-- Appending `?mode=ro` to the dsn opens the file read-only as well.
+--
+-- Annotations:
+--   sqlite/readonly: "true" to open the file read-only, through the `file:<path>?mode=ro` URI.
+--
+-- Runtime args are appended to the client command, e.g. `dbhub c my-sqlite -- "select count(*) from users"`.
+
+assert(dbhub ~= nil, "dbhub is not defined")
+assert(dbhub.variables ~= nil, "dbhub.variables is not defined")
+assert(dbhub.annotations ~= nil, "dbhub.annotations is not defined")
+assert(dbhub.runtime_args ~= nil, "dbhub.runtime_args is not defined")
+
+local variables = dbhub.variables
+local annotations = dbhub.annotations
+
+local readonly_key = "sqlite/readonly"
+
+local shell_quote = dbhub.shell_quote
+local prelude = require("dbhub")
+local is_true = prelude.is_true
+
+local function expand_path(path)
+    if path == "" or path == ":memory:" then
+        return path
+    end
+    if path == "~" or path:sub(1, 2) == "~/" then
+        return dbhub.expand_home(path)
+    end
+    -- absolute unix or windows path
+    if path:sub(1, 1) == "/" or path:match("^%a:[/\\]") then
+        return path
+    end
+    -- getenv is nil in the sandbox without `os`, the client runs in the current directory anyway
+    local cwd = dbhub.getenv and dbhub.getenv("PWD")
+    if cwd == nil or cwd == "" then
+        return path
+    end
+    return cwd .. "/" .. path:gsub("^%./", "")
+end
+
+-- path?mode=ro -> path, { mode = "ro" }
+local path, query = (variables.path or ""):match("^([^?]*)%??(.*)$")
+local options = dbhub.parse_query(query)
+
+path = expand_path(path)
+local readonly = is_true(annotations[readonly_key]) or options.mode == "ro" or is_true(options.readonly)
+
+local target = path
+if readonly and path ~= "" then
+    -- `%`, `?` and `#` are special in URI filenames
+    local escaped = path:gsub("[%%?#]", function(c) return string.format("%%%02X", string.byte(c)) end)
+    target = "file:" .. escaped .. "?mode=ro"
+end
+
+local args = "sqlite3"
+if target ~= "" then
+    args = args .. " " .. shell_quote(target)
+end
+args = args .. prelude.runtime_args()
+
+return {
+    command_with_args = args,
+    again = false
+}
@@ -1,20 +1,36 @@
 //! Command generation from Lua scripts.
 
 use crate::config::{Config, Database};
-use crate::embedded::Scripts;
 use color_eyre::eyre::{eyre, Result};
 use std::{
     collections::HashMap,
-    path,
+    fs,
+    io::{Read, Write},
+    path::PathBuf,
+    process::Stdio,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-use super::lua::{execute_lua, LuaContext, LuaOutput};
+use super::lua::{execute_lua, LuaContext, LuaOutput, LuaScript};
+use super::script::{lib_dirs, resolve_script};
 
-/// Maximum number of Lua script execution iterations.
+/// Maximum number of Lua script execution iterations, unless the script sets `max_iterations`.
 /// This prevents infinite loops caused by Lua scripts returning `again = true`.
 const MAX_LUA_ITERATIONS: usize = 5;
 
+/// Upper bound of the `max_iterations` a script can set.
+const MAX_LUA_ITERATIONS_LIMIT: usize = 50;
+
+/// How often a step with a timeout is checked for completion.
+const STEP_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long the output of a timed out step is still read, a process that left the step's
+/// process group may keep its pipes open.
+const STEP_OUTPUT_GRACE: Duration = Duration::from_millis(100);
+
 /// A parsed connection command ready for execution.
 #[derive(Debug, Clone)]
 pub struct ConnectCommand {
@@ -22,6 +38,12 @@ pub struct ConnectCommand {
     pub command: String,
     /// Arguments to pass to the command
     pub args: Vec<String>,
+    /// Environment variables to set for the command
+    pub env: HashMap<String, String>,
+    /// Text written to the command's standard input, which is then closed
+    pub stdin: Option<String>,
+    /// Text written to a private temporary file whose path replaces `{init_file}` in the arguments
+    pub init_file: Option<String>,
 }
 
 /// Build a connection command for the given database.
@@ -44,21 +66,12 @@ pub fn build_connect_command(
     cfg: &Config,
     passthrough_args: &[String],
 ) -> Result<ConnectCommand> {
-    let template = cfg.get_templates().get(db.db_type.as_str())
-        .ok_or_else(|| eyre!("No template found for database type: {}", db.db_type))?;
-
-    let (variables, annotations) = db.variables(&template.dsn)?;
-    let lua_script_path = locate_lua_script(db.db_type.as_str())?;
+    let (variables, annotations) = cfg.variables(db)?;
+    let lua_script = resolve_script(cfg, db)?;
 
     // Execute Lua script to generate command
-    let output = execute_lua_once(
-        &lua_script_path,
-        &variables,
-        &annotations,
-        &[],
-        0,
-        passthrough_args,
-    )?;
+    let context = LuaContext::new(variables, annotations, passthrough_args.to_vec());
+    let output = execute_lua_once(&lua_script, &context, cfg)?;
 
     let args = shell_words::split(&output.command_with_args)?;
     let command = args.first()
@@ -68,127 +81,379 @@ pub fn build_connect_command(
     Ok(ConnectCommand {
         command,
         args: args[1..].to_vec(),
+        env: output.env,
+        stdin: output.stdin,
+        init_file: output.init_file,
     })
 }
 
 /// Execute Lua script once and return the output.
-fn execute_lua_once(
-    lua_script_path: &path::Path,
-    variables: &HashMap<String, String>,
-    annotations: &HashMap<String, String>,
-    last_output_lines: &[String],
-    count: usize,
-    runtime_args: &[String],
-) -> Result<LuaOutput> {
-    let context = LuaContext {
-        count,
-        variables: variables.clone(),
-        annotations: annotations.clone(),
-        last_output_lines: last_output_lines.to_vec(),
-        runtime_args: runtime_args.to_vec(),
-    };
-
-    execute_lua(lua_script_path, &context)
+fn execute_lua_once(lua_script: &LuaScript, context: &LuaContext, cfg: &Config) -> Result<LuaOutput> {
+    execute_lua(lua_script, context, cfg.sandbox.as_ref(), &lib_dirs(&cfg.script_dirs()))
 }
 
 /// Run the Lua script iteratively until it returns `again = false` or reaches max iterations.
 ///
-/// Returns the final command arguments.
+/// Each step sees the stdout, stderr and exit code of the previous command and the
+/// `dbhub.state` it left. Returns the final command.
 pub(super) fn run_lua_iterative(
     db: &Database,
     cfg: &Config,
     passthrough_args: &[String],
-) -> Result<(String, Vec<String>)> {
-    let template = cfg.get_templates().get(db.db_type.as_str())
-        .ok_or_else(|| eyre!("No template found for database type: {}", db.db_type))?;
-
-    let (variables, annotations) = db.variables(&template.dsn)?;
-    let lua_script_path = locate_lua_script(db.db_type.as_str())?;
+) -> Result<ConnectCommand> {
+    let (variables, annotations) = cfg.variables(db)?;
+    let lua_script = resolve_script(cfg, db)?;
 
-    let mut last_output_lines: Vec<String> = vec![];
-    let mut counter = 0;
+    let mut context = LuaContext::new(variables, annotations, passthrough_args.to_vec());
+    let mut max_iterations = MAX_LUA_ITERATIONS;
 
-    while counter < MAX_LUA_ITERATIONS {
-        let output = execute_lua_once(
-            &lua_script_path,
-            &variables,
-            &annotations,
-            &last_output_lines,
-            counter,
-            passthrough_args,
-        )?;
+    while context.count < max_iterations {
+        let output = execute_lua_once(&lua_script, &context, cfg)?;
+        if let Some(limit) = output.max_iterations {
+            if limit > MAX_LUA_ITERATIONS_LIMIT {
+                warn!("max_iterations {} is capped at {}", limit, MAX_LUA_ITERATIONS_LIMIT);
+            }
+            max_iterations = limit.min(MAX_LUA_ITERATIONS_LIMIT);
+        }
 
-        info!("#{} Running command: \n\n\t💻 -> {}\n", counter, output.command_with_args);
+        info!("#{} Running command: \n\n\t💻 -> {}\n", context.count, output.command_with_args);
 
         let args = shell_words::split(&output.command_with_args)?;
         let command = args.first()
             .ok_or_else(|| eyre!("No command provided"))?
             .clone();
 
-        // Verify command exists
-        which::which(&command).map_err(|_| {
-            eyre!("Command `{}` not found, please install it or check PATH.", command)
-        })?;
-
-        counter += 1;
-        last_output_lines.clear();
-
-        if output.again {
-            // Execute command and capture output for next iteration
-            let exec_output = std::process::Command::new(&command)
-                .args(&args[1..])
-                .stdout(std::process::Stdio::piped())
-                .stderr(std::process::Stdio::piped())
-                .spawn()?
-                .wait_with_output()?;
-
-            if !exec_output.status.success() {
-                return Err(eyre!(
-                    "Command `{}` failed: {}",
-                    command,
-                    String::from_utf8(exec_output.stderr)?
-                ));
+        if !output.again {
+            // Verify command exists
+            which::which(&command).map_err(|_| {
+                eyre!("Command `{}` not found, please install it or check PATH.", command)
+            })?;
+
+            return Ok(ConnectCommand {
+                command,
+                args: args[1..].to_vec(),
+                env: output.env,
+                stdin: output.stdin,
+                init_file: output.init_file,
+            });
+        }
+
+        // Execute command and capture output for next iteration
+        let step = match run_step(&command, &args[1..], &output) {
+            Ok(step) => step,
+            Err(e) if output.allow_failure => StepOutput::failed(e.to_string()),
+            Err(e) => return Err(e),
+        };
+
+        if step.exit_code != Some(0) {
+            let reason = match (step.timed_out, output.timeout_ms) {
+                (true, Some(timeout_ms)) => format!("timed out after {timeout_ms} ms"),
+                _ => format!("failed: {}", step.stderr.trim()),
+            };
+            if !output.allow_failure {
+                return Err(eyre!("Command `{}` {}", command, reason));
             }
+            warn!("#{} Command `{}` {}, running the script again", context.count, command, reason);
+        }
 
-            debug!("Command `{}` output: \n{}", command,
-                String::from_utf8(exec_output.stdout.clone())?);
+        debug!("Command `{}` output: \n{}", command, step.stdout);
 
-            for line in String::from_utf8(exec_output.stdout)?.trim().split('\n') {
-                last_output_lines.push(line.to_string());
+        context = LuaContext {
+            count: context.count + 1,
+            last_output_lines: output_lines(&step.stdout),
+            last_exit_code: step.exit_code,
+            last_stderr_lines: output_lines(&step.stderr),
+            state: output.state,
+            ..context
+        };
+    }
+
+    Err(eyre!("Script execution exceeded {} iterations", max_iterations))
+}
+
+/// Output of a command run for a step with `again = true`.
+struct StepOutput {
+    /// `None` when the command could not run, was killed or timed out.
+    exit_code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    timed_out: bool,
+}
+
+impl StepOutput {
+    /// A step whose command could not be started, e.g. because it is not installed.
+    fn failed(error: String) -> Self {
+        StepOutput { exit_code: None, stdout: String::new(), stderr: error, timed_out: false }
+    }
+}
+
+/// Run the command of a step, capturing its output and killing it after `timeout_ms`.
+fn run_step(command: &str, args: &[String], output: &LuaOutput) -> Result<StepOutput> {
+    which::which(command).map_err(|_| {
+        eyre!("Command `{}` not found, please install it or check PATH.", command)
+    })?;
+
+    let init_file = output.init_file.as_deref().map(InitFile::create).transpose()?;
+    let args = InitFile::substitute(init_file.as_ref(), args);
+
+    let mut step = std::process::Command::new(command);
+    step.args(&args)
+        .envs(&output.env)
+        .stdin(if output.stdin.is_some() { Stdio::piped() } else { Stdio::inherit() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // A step with a timeout runs in its own process group, so that the processes it started
+    // are killed with it and do not keep its pipes open.
+    #[cfg(unix)]
+    if output.timeout_ms.is_some() {
+        std::os::unix::process::CommandExt::process_group(&mut step, 0);
+    }
+    let mut child = step.spawn()?;
+
+    // Read both pipes while waiting, a command filling one of them would block otherwise.
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+    // The input is written from its own thread as the command may not read it, or only after
+    // writing its output. The pipe is closed once written, a command that exited early is fine.
+    if let (Some(input), Some(mut stdin)) = (output.stdin.clone(), child.stdin.take()) {
+        thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        });
+    }
+
+    let status = match output.timeout_ms {
+        None => Some(child.wait()?),
+        Some(timeout_ms) => {
+            let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+            loop {
+                if let Some(status) = child.try_wait()? {
+                    break Some(status);
+                }
+                if Instant::now() >= deadline {
+                    kill_process_group(&mut child)?;
+                    child.wait()?;
+                    break None;
+                }
+                thread::sleep(STEP_POLL_INTERVAL);
             }
-            continue;
         }
+    };
 
-        return Ok((command, args[1..].to_vec()));
+    let read = |pipe: mpsc::Receiver<Vec<u8>>| {
+        let buf = match status {
+            Some(_) => pipe.recv().ok(),
+            None => pipe.recv_timeout(STEP_OUTPUT_GRACE).ok(),
+        };
+        String::from_utf8_lossy(&buf.unwrap_or_default()).to_string()
+    };
+    Ok(StepOutput {
+        exit_code: status.and_then(|status| status.code()),
+        stdout: read(stdout),
+        stderr: read(stderr),
+        timed_out: status.is_none(),
+    })
+}
+
+/// Kill a step and, on unix, the processes it started in its process group.
+fn kill_process_group(child: &mut std::process::Child) -> std::io::Result<()> {
+    #[cfg(unix)]
+    if let Ok(pid) = libc::pid_t::try_from(child.id()) {
+        // SAFETY: `kill` has no memory safety requirements, the group is the one created for the step.
+        unsafe { libc::kill(-pid, libc::SIGKILL) };
     }
+    child.kill()
+}
 
-    Err(eyre!("Script execution exceeded {} iterations", MAX_LUA_ITERATIONS))
+/// A temporary file holding the script's `init_file`, readable by the current user only
+/// and removed when dropped, i.e. once the command has exited.
+pub(super) struct InitFile {
+    path: PathBuf,
 }
 
-/// Locate the Lua script for a database type.
-///
-/// If the script doesn't exist in ~/.dbhub/, it will be copied from embedded resources.
-fn locate_lua_script(db_type: &str) -> Result<path::PathBuf> {
-    let home_dir = dirs::home_dir()
-        .ok_or_else(|| eyre!("Failed to get home directory"))?;
+impl InitFile {
+    /// The placeholder replaced with the path of the file in the command's arguments.
+    const PLACEHOLDER: &'static str = "{init_file}";
+
+    pub(super) fn create(content: &str) -> Result<Self> {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or_default();
+        let path = std::env::temp_dir().join(format!("dbhub-{}-{}.sql", std::process::id(), nanos));
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&path)
+            .map_err(|e| eyre!("Failed to create '{}': {}", path.display(), e))?;
+        // Created first so that a failed write still removes the file.
+        let init_file = InitFile { path };
+        file.write_all(content.as_bytes())?;
+        Ok(init_file)
+    }
+
+    /// Replace `{init_file}` in the arguments with the path of the file.
+    pub(super) fn substitute(init_file: Option<&Self>, args: &[String]) -> Vec<String> {
+        match init_file {
+            Some(init_file) => {
+                let path = init_file.path.to_string_lossy();
+                args.iter().map(|arg| arg.replace(Self::PLACEHOLDER, &path)).collect()
+            }
+            None => args.to_vec(),
+        }
+    }
+}
 
-    let lua_script = format!("{db_type}.lua");
-    let lua_script_path = home_dir.join(".dbhub").join(&lua_script);
+impl Drop for InitFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
 
-    if !lua_script_path.exists() {
-        debug!("Lua script not found at {:?}, copying from embedded resources", lua_script_path);
+/// Read a pipe to the end in a thread, the output is sent once the pipe is closed.
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> mpsc::Receiver<Vec<u8>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        let _ = sender.send(buf);
+    });
+    receiver
+}
 
-        let embedded_file = Scripts::get(&lua_script)
-            .ok_or_else(|| eyre!("No embedded Lua script found for database type: {}", db_type))?;
+/// Split command output into lines for `last_output_lines` and `last_stderr_lines`.
+fn output_lines(output: &str) -> Vec<String> {
+    output.trim().lines().map(str::to_string).collect()
+}
 
-        let parent_dir = lua_script_path.parent()
-            .ok_or_else(|| eyre!("Invalid Lua script path: no parent directory"))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(script: &str) -> Config {
+        let script = script.lines().map(|line| format!("      {line}\n")).collect::<String>();
+        serde_yaml::from_str(&format!(r#"
+databases:
+  - alias: steps
+    db_type: sqlite
+    env: local
+    dsn: "sqlite:///tmp/steps.db"
+    script: |
+{script}
+templates:
+  sqlite:
+    dsn: sqlite://{{path}}
+"#)).unwrap()
+    }
 
-        std::fs::create_dir_all(parent_dir)?;
-        std::fs::write(&lua_script_path, embedded_file.data.as_ref())?;
+    #[test]
+    fn test_build_connect_command_vars_only() {
+        let cfg: Config = serde_yaml::from_str(r#"
+databases:
+  - alias: partial-mongo
+    db_type: mongo
+    env: local
+    vars: { user: u, password: p, host: db, port: "27017" }
+templates:
+  mongo:
+    dsn: mongodb://{user}:{password}@{host}:{port}/{database}?{query}
+"#).unwrap();
+        let command = build_connect_command(&cfg.databases[0], &cfg, &[]).unwrap();
+        assert_eq!(command.args, vec!["mongodb://u:p@db:27017/admin?"]);
+    }
+
+    #[test]
+    fn test_run_lua_iterative_fallback() {
+        let cfg = config(r#"
+local state = dbhub.state
+if dbhub.count == 0 then
+    return { command_with_args = "sh -c 'echo primary down >&2; exit 3'", again = true, allow_failure = true }
+elseif dbhub.count == 1 then
+    state.primary = { code = dbhub.last_exit_code, error = dbhub.last_stderr_lines[1] }
+    return { command_with_args = "sleep 5", again = true, allow_failure = true, timeout_ms = 100 }
+elseif dbhub.count == 2 then
+    state.slow = dbhub.last_exit_code == nil
+    return { command_with_args = "echo replica", again = true }
+end
+assert(state.primary.code == 3 and state.primary.error == "primary down" and state.slow)
+return { command_with_args = "sh -c " .. dbhub.shell_quote("connect " .. dbhub.last_output_lines[1]), again = false }
+"#);
+        let command = run_lua_iterative(&cfg.databases[0], &cfg, &[]).unwrap();
+        assert_eq!(command.command, "sh");
+        assert_eq!(command.args, vec!["-c", "connect replica"]);
+
+        // a failing step without allow_failure stops the loop
+        let cfg = config(r#"return { command_with_args = "sh -c 'exit 2'", again = true }"#);
+        let err = run_lua_iterative(&cfg.databases[0], &cfg, &[]).unwrap_err();
+        assert!(err.to_string().contains("Command `sh` failed"), "{err}");
+
+        let cfg = config(r#"return { command_with_args = "sleep 5", again = true, timeout_ms = 50 }"#);
+        let err = run_lua_iterative(&cfg.databases[0], &cfg, &[]).unwrap_err();
+        assert!(err.to_string().contains("Command `sleep` timed out after 50 ms"), "{err}");
+
+        let cfg = config(r#"return { command_with_args = "true", again = true, max_iterations = 2 }"#);
+        let err = run_lua_iterative(&cfg.databases[0], &cfg, &[]).unwrap_err();
+        assert!(err.to_string().contains("exceeded 2 iterations"), "{err}");
+
+        let cfg = config(r#"return { command_with_args = "true", again = true, max_iterations = 1000000 }"#);
+        let err = run_lua_iterative(&cfg.databases[0], &cfg, &[]).unwrap_err();
+        assert!(err.to_string().contains(&format!("exceeded {MAX_LUA_ITERATIONS_LIMIT} iterations")), "{err}");
+    }
 
-        info!("Created Lua script at: {:?}", lua_script_path);
+    #[test]
+    #[cfg(unix)]
+    fn test_step_stdin() {
+        // more input than a pipe holds, to a command writing its output first and one not reading it
+        let cfg = config(r#"
+local input = string.rep("x", 1024 * 1024) .. "\n"
+if dbhub.count == 0 then
+    return { command_with_args = "sh -c 'head -c 1048576 /dev/zero; wc -c'", again = true, stdin = input }
+elseif dbhub.count == 1 then
+    assert(dbhub.last_output_lines[1]:match("1048577$"), dbhub.last_output_lines[1])
+    return { command_with_args = "echo skipped", again = true, stdin = input }
+elseif dbhub.count == 2 then
+    assert(dbhub.last_output_lines[1] == "skipped")
+    return { command_with_args = "sleep 5", again = true, stdin = input, timeout_ms = 100, allow_failure = true }
+end
+assert(dbhub.last_exit_code == nil)
+return { command_with_args = "true", again = false }
+"#);
+        let started = Instant::now();
+        run_lua_iterative(&cfg.databases[0], &cfg, &[]).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(4), "{:?}", started.elapsed());
     }
 
-    Ok(lua_script_path)
+    #[test]
+    #[cfg(unix)]
+    fn test_timeout_kills_started_processes() {
+        // the shell's `sleep` keeps the pipes open unless the whole process group is killed
+        let cfg = config(r#"return { command_with_args = "sh -c 'sleep 30; echo done'", again = true, timeout_ms = 100 }"#);
+        let started = Instant::now();
+        let err = run_lua_iterative(&cfg.databases[0], &cfg, &[]).unwrap_err();
+        assert!(err.to_string().contains("timed out after 100 ms"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(5), "{:?}", started.elapsed());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_init_file() {
+        let cfg = config(r#"
+if dbhub.count == 0 then
+    return { command_with_args = "sh -c 'cat \"$1\"; stat -c %a \"$1\"' sh {init_file}", again = true, init_file = "connect\n" }
+end
+assert(dbhub.last_output_lines[1] == "connect" and dbhub.last_output_lines[2] == "600")
+return { command_with_args = "true", again = false }
+"#);
+        run_lua_iterative(&cfg.databases[0], &cfg, &[]).unwrap();
+
+        let init_file = InitFile::create("connect").unwrap();
+        let path = init_file.path.clone();
+        let args = InitFile::substitute(Some(&init_file), &["@{init_file}".to_string()]);
+        assert_eq!(args, vec![format!("@{}", path.display())]);
+        drop(init_file);
+        assert!(!path.exists());
+    }
 }
@@ -1,8 +1,9 @@
 //! Interactive connection execution.
 
-use super::command::run_lua_iterative;
+use super::command::{run_lua_iterative, InitFile};
 use crate::config::{Config, Database};
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
+use std::{io::Write, process::Stdio};
 use tracing::info;
 
 /// Connect to a database interactively.
@@ -10,6 +11,10 @@ use tracing::info;
 /// This function runs the connection command in the current terminal,
 /// inheriting stdin/stdout/stderr for interactive sessions.
 ///
+/// A script setting `stdin` gets a non-interactive session: the text is written to a pipe
+/// which is then closed. Initial input for an interactive client goes through `init_file`,
+/// e.g. `sqlplus /nolog @{init_file}`, the file is removed once the client exits.
+///
 /// # Arguments
 ///
 /// * `db` - The database connection information
@@ -20,17 +25,94 @@ use tracing::info;
 ///
 /// Returns `Ok(())` on successful connection completion.
 pub fn connect(db: &Database, cfg: &Config, passthrough_args: &[String]) -> Result<()> {
-    let (command, args) = run_lua_iterative(db, cfg, passthrough_args)?;
+    let command = run_lua_iterative(db, cfg, passthrough_args)?;
+
+    let init_file = command.init_file.as_deref().map(InitFile::create).transpose()?;
+    let args = InitFile::substitute(init_file.as_ref(), &command.args);
 
     // Execute interactively
-    std::process::Command::new(&command)
+    let mut child = std::process::Command::new(&command.command)
         .args(&args)
-        .stdin(std::process::Stdio::inherit())
-        .stdout(std::process::Stdio::inherit())
-        .stderr(std::process::Stdio::inherit())
-        .spawn()?
-        .wait()?;
+        .envs(&command.env)
+        .stdin(if command.stdin.is_some() { Stdio::piped() } else { Stdio::inherit() })
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+
+    // The pipe is closed once the input is written, the command sees the end of its input.
+    if let (Some(input), Some(mut stdin)) = (command.stdin, child.stdin.take()) {
+        stdin.write_all(input.as_bytes())?;
+    }
+    child.wait()?;
+    drop(init_file);
 
     info!("Connection closed.");
     Ok(())
 }
+
+/// Run an arbitrary command with the connection's environment variables.
+///
+/// The variables from `Config::env_vars` (e.g. `DATABASE_URL`) are only set
+/// for the child process, the current environment is left untouched.
+///
+/// # Arguments
+///
+/// * `db` - The database connection information
+/// * `cfg` - The configuration containing templates
+/// * `command_with_args` - The program to run followed by its arguments
+///
+/// # Returns
+///
+/// Returns the exit code of the command, `128 + signal` when it was killed by a signal.
+///
+/// The variables are taken from the config as they are: secrets are not resolved and no
+/// tunnel is set up around the command.
+pub fn run(db: &Database, cfg: &Config, command_with_args: &[String]) -> Result<i32> {
+    let (command, args) = command_with_args.split_first()
+        .ok_or_else(|| eyre!("No command provided"))?;
+
+    which::which(command).map_err(|_| {
+        eyre!("Command `{}` not found, please install it or check PATH.", command)
+    })?;
+
+    let env_vars = cfg.env_vars(db)?;
+
+    let status = std::process::Command::new(command)
+        .args(args)
+        .envs(&env_vars)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .spawn()?
+        .wait()?;
+
+    info!("Command `{}` exited with {}", command, status);
+    Ok(exit_code(status))
+}
+
+/// The exit code of a command, following the shell convention of `128 + signal`
+/// for commands terminated by a signal.
+fn exit_code(status: std::process::ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_exit_code() {
+        let status = |script: &str| std::process::Command::new("sh").args(["-c", script]).status().unwrap();
+        assert_eq!(exit_code(status("exit 3")), 3);
+        assert_eq!(exit_code(status("kill -TERM $$")), 143);
+        assert_eq!(exit_code(status("kill -KILL $$")), 137);
+    }
+}
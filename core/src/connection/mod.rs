@@ -3,6 +3,11 @@
 mod command;
 mod executor;
 mod lua;
+mod net;
+mod script;
 
 pub use command::{build_connect_command, ConnectCommand};
-pub use executor::connect;
+pub use executor::{connect, run};
+pub use lua::{LuaContext, LuaOutput, LuaScript};
+pub(crate) use lua::url_decode;
+pub use script::{diff_script, list_scripts, override_script, reset_script, test_script, ScriptInfo, ScriptStatus};
@@ -0,0 +1,446 @@
+//! Network primitives for Lua scripts, so discovery steps don't need a client installed.
+
+use color_eyre::eyre::{eyre, Result};
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// DNS record type of SRV records.
+const DNS_TYPE_SRV: u16 = 33;
+
+/// Compression pointers followed while reading a DNS name, more means a loop.
+const DNS_MAX_POINTERS: usize = 64;
+
+/// A DNS SRV record, e.g. `_mongodb._tcp.cluster0.example.com`.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct SrvRecord {
+    pub target: String,
+    pub port: u16,
+    pub priority: u16,
+    pub weight: u16,
+}
+
+/// A response of `http_get`, header names are lower case.
+#[derive(Debug)]
+pub(super) struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Send `data` to `host:port` and read the response until the peer closes the connection,
+/// `complete` returns true for the data read so far, or `max_bytes` are read.
+pub(super) fn tcp_request(
+    host: &str,
+    port: u16,
+    data: &[u8],
+    timeout: Duration,
+    max_bytes: usize,
+    mut complete: impl FnMut(&[u8]) -> Result<bool>,
+) -> Result<Vec<u8>> {
+    let deadline = Instant::now() + timeout;
+    let mut stream = connect(host, port, timeout)?;
+    stream.set_write_timeout(Some(timeout))?;
+    stream.write_all(data)
+        .map_err(|e| eyre!("could not send to {}:{}: {}", host, port, e))?;
+
+    let timed_out = || eyre!("timed out after {} ms waiting for {}:{}", timeout.as_millis(), host, port);
+    let mut response = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        let remaining = deadline.checked_duration_since(Instant::now())
+            .filter(|remaining| !remaining.is_zero())
+            .ok_or_else(timed_out)?;
+        stream.set_read_timeout(Some(remaining))?;
+        let n = match stream.read(&mut buf) {
+            Ok(n) => n,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => return Err(timed_out()),
+            Err(e) => return Err(eyre!("could not read from {}:{}: {}", host, port, e)),
+        };
+        if n == 0 {
+            return Ok(response);
+        }
+        response.extend_from_slice(&buf[..n]);
+        if response.len() >= max_bytes {
+            response.truncate(max_bytes);
+            return Ok(response);
+        }
+        if complete(&response)? {
+            return Ok(response);
+        }
+    }
+}
+
+/// Look up the SRV records of `name`, sorted by priority and then by weight, heaviest first.
+///
+/// `server` is an `ip` or `ip:port`, the first nameserver of `/etc/resolv.conf` by default.
+/// An unknown name has no records.
+pub(super) fn dns_srv(name: &str, server: Option<&str>, timeout: Duration) -> Result<Vec<SrvRecord>> {
+    let server = match server {
+        Some(server) => parse_nameserver(server)?,
+        None => system_nameserver()?,
+    };
+    let id = query_id();
+    let query = dns_query(id, name, DNS_TYPE_SRV)?;
+
+    let socket = UdpSocket::bind(if server.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" })?;
+    socket.send_to(&query, server)
+        .map_err(|e| eyre!("could not send the DNS query to {}: {}", server, e))?;
+
+    let deadline = Instant::now() + timeout;
+    let mut buf = [0u8; 4096];
+    let response = loop {
+        let remaining = deadline.checked_duration_since(Instant::now())
+            .filter(|remaining| !remaining.is_zero())
+            .ok_or_else(|| eyre!("DNS query for {} to {} timed out after {} ms", name, server, timeout.as_millis()))?;
+        socket.set_read_timeout(Some(remaining))?;
+        let (n, from) = match socket.recv_from(&mut buf) {
+            Ok(received) => received,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(eyre!("could not read the DNS response from {}: {}", server, e)),
+        };
+        // ignore stray datagrams, e.g. late answers to an earlier query
+        if from == server && n >= 2 && buf[..2] == id.to_be_bytes() {
+            break buf[..n].to_vec();
+        }
+    };
+
+    // a truncated answer is asked again over TCP, the message is prefixed with its length
+    let response = if response.len() > 2 && response[2] & 0x02 != 0 {
+        let mut request = (query.len() as u16).to_be_bytes().to_vec();
+        request.extend_from_slice(&query);
+        let remaining = deadline.saturating_duration_since(Instant::now()).max(Duration::from_millis(1));
+        let host = server.ip().to_string();
+        let framed = tcp_request(&host, server.port(), &request, remaining, usize::from(u16::MAX) + 2, |data| {
+            Ok(data.len() >= 2 && data.len() >= 2 + usize::from(u16::from_be_bytes([data[0], data[1]])))
+        })?;
+        framed.get(2..).unwrap_or_default().to_vec()
+    } else {
+        response
+    };
+
+    let mut records = parse_srv_response(id, &response)?;
+    records.sort_by(|a, b| a.priority.cmp(&b.priority).then(b.weight.cmp(&a.weight)));
+    Ok(records)
+}
+
+/// Send a GET request to an `http://` url, credentials go into `headers`, e.g. `Authorization`.
+pub(super) fn http_get(
+    url: &str,
+    headers: &[(String, String)],
+    timeout: Duration,
+    max_bytes: usize,
+) -> Result<HttpResponse> {
+    let rest = url.strip_prefix("http://").ok_or_else(|| {
+        if url.starts_with("https://") {
+            eyre!("https is not supported, only http:// urls")
+        } else {
+            eyre!("invalid url {}, expected http://host[:port]/path", url)
+        }
+    })?;
+    let (authority, path) = match rest.find(['/', '?']) {
+        Some(i) if rest[i..].starts_with('?') => (&rest[..i], format!("/{}", &rest[i..])),
+        Some(i) => (&rest[..i], rest[i..].to_string()),
+        None => (rest, "/".to_string()),
+    };
+    if authority.contains('@') {
+        return Err(eyre!("credentials in the url are not supported, set an Authorization header instead"));
+    }
+    let (host, port) = split_host_port(authority, 80)?;
+
+    let mut request = format!("GET {path} HTTP/1.1\r\nHost: {authority}\r\nUser-Agent: dbhub\r\nAccept: */*\r\nConnection: close\r\n");
+    for (name, value) in headers {
+        if [name, value].iter().any(|s| s.contains(['\r', '\n'])) {
+            return Err(eyre!("invalid header {}, it must not contain line breaks", name));
+        }
+        request.push_str(&format!("{name}: {value}\r\n"));
+    }
+    request.push_str("\r\n");
+
+    let response = tcp_request(&host, port, request.as_bytes(), timeout, max_bytes, |_| Ok(false))?;
+    parse_http_response(&response)
+}
+
+/// Connect to `host:port`, trying each address the host resolves to.
+fn connect(host: &str, port: u16, timeout: Duration) -> Result<TcpStream> {
+    let addrs = (host, port).to_socket_addrs()
+        .map_err(|e| eyre!("could not resolve {}: {}", host, e))?;
+    let mut last_error = None;
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    match last_error {
+        Some(e) => Err(eyre!("could not connect to {}:{}: {}", host, port, e)),
+        None => Err(eyre!("could not resolve {}", host)),
+    }
+}
+
+/// `host:port`, `[::1]:port` or `host` with the default port.
+fn split_host_port(authority: &str, default_port: u16) -> Result<(String, u16)> {
+    let invalid = || eyre!("invalid host {}", authority);
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(rest) => {
+            let (host, port) = rest.split_once(']').ok_or_else(invalid)?;
+            (host, port.strip_prefix(':'))
+        }
+        None => match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    if host.is_empty() {
+        return Err(invalid());
+    }
+    let port = match port {
+        Some(port) => port.parse().map_err(|_| invalid())?,
+        None => default_port,
+    };
+    Ok((host.to_string(), port))
+}
+
+fn parse_http_response(response: &[u8]) -> Result<HttpResponse> {
+    let header_end = response.windows(4).position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| eyre!("incomplete HTTP response"))?;
+    let head = String::from_utf8_lossy(&response[..header_end]);
+    let mut lines = head.split("\r\n");
+
+    let status_line = lines.next().unwrap_or_default();
+    let status = status_line.split_whitespace().nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| eyre!("invalid HTTP status line: {}", status_line))?;
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    let header = |name: &str| headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+
+    let body = &response[header_end + 4..];
+    let body = if header("transfer-encoding").is_some_and(|value| value.eq_ignore_ascii_case("chunked")) {
+        decode_chunked(body)?
+    } else if let Some(length) = header("content-length").and_then(|value| value.parse::<usize>().ok()) {
+        body.get(..length).ok_or_else(|| eyre!("incomplete HTTP body, expected {} bytes", length))?.to_vec()
+    } else {
+        body.to_vec()
+    };
+
+    Ok(HttpResponse { status, headers, body })
+}
+
+fn decode_chunked(mut data: &[u8]) -> Result<Vec<u8>> {
+    let incomplete = || eyre!("incomplete chunked HTTP body");
+    let mut body = Vec::new();
+    loop {
+        let line_end = data.windows(2).position(|window| window == b"\r\n").ok_or_else(incomplete)?;
+        let size_line = String::from_utf8_lossy(&data[..line_end]);
+        let size_hex = size_line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size_hex, 16)
+            .map_err(|_| eyre!("invalid chunk size {:?}", size_hex))?;
+        data = &data[line_end + 2..];
+        if size == 0 {
+            return Ok(body);
+        }
+        body.extend_from_slice(data.get(..size).ok_or_else(incomplete)?);
+        data = data.get(size + 2..).ok_or_else(incomplete)?;
+    }
+}
+
+/// `ip` or `ip:port`, port 53 by default.
+fn parse_nameserver(server: &str) -> Result<SocketAddr> {
+    server.parse::<SocketAddr>()
+        .or_else(|_| server.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 53)))
+        .map_err(|_| eyre!("invalid DNS server {}, expected an ip or ip:port", server))
+}
+
+/// The first nameserver of `/etc/resolv.conf`.
+fn system_nameserver() -> Result<SocketAddr> {
+    let resolv_conf = std::fs::read_to_string("/etc/resolv.conf").unwrap_or_default();
+    resolv_conf.lines()
+        .filter_map(|line| line.trim().strip_prefix("nameserver"))
+        .filter_map(|server| server.trim().parse::<IpAddr>().ok())
+        .map(|ip| SocketAddr::new(ip, 53))
+        .next()
+        .ok_or_else(|| eyre!("no nameserver found in /etc/resolv.conf, pass one with `server`"))
+}
+
+/// A query id that is hard enough to guess for answers from the local network.
+fn query_id() -> u16 {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or_default();
+    (nanos ^ (nanos >> 16) ^ std::process::id()) as u16
+}
+
+/// A recursive DNS query for one name.
+fn dns_query(id: u16, name: &str, record_type: u16) -> Result<Vec<u8>> {
+    let mut query = Vec::with_capacity(name.len() + 18);
+    query.extend_from_slice(&id.to_be_bytes());
+    // recursion desired, one question
+    query.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(eyre!("invalid DNS name {}", name));
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    if query.len() > 12 + 255 {
+        return Err(eyre!("DNS name {} is too long", name));
+    }
+    query.extend_from_slice(&record_type.to_be_bytes());
+    query.extend_from_slice(&1u16.to_be_bytes());
+    Ok(query)
+}
+
+fn parse_srv_response(id: u16, msg: &[u8]) -> Result<Vec<SrvRecord>> {
+    let invalid = || eyre!("invalid DNS response");
+    let u16_at = |pos: usize| msg.get(pos..pos + 2).map(|b| u16::from_be_bytes([b[0], b[1]])).ok_or_else(invalid);
+
+    if u16_at(0)? != id {
+        return Err(eyre!("DNS response does not match the query"));
+    }
+    let flags = u16_at(2)?;
+    match flags & 0x000f {
+        0 => {}
+        3 => return Ok(Vec::new()),
+        rcode => return Err(eyre!("DNS query failed with response code {}", rcode)),
+    }
+
+    let mut pos = 12;
+    for _ in 0..u16_at(4)? {
+        pos = read_dns_name(msg, pos)?.1 + 4;
+    }
+    let mut records = Vec::new();
+    for _ in 0..u16_at(6)? {
+        pos = read_dns_name(msg, pos)?.1;
+        let record_type = u16_at(pos)?;
+        let data_len = usize::from(u16_at(pos + 8)?);
+        let data = pos + 10;
+        if record_type == DNS_TYPE_SRV {
+            records.push(SrvRecord {
+                priority: u16_at(data)?,
+                weight: u16_at(data + 2)?,
+                port: u16_at(data + 4)?,
+                target: read_dns_name(msg, data + 6)?.0,
+            });
+        }
+        pos = data + data_len;
+    }
+    Ok(records)
+}
+
+/// Read a possibly compressed name at `pos`, returns the name and the position after it.
+fn read_dns_name(msg: &[u8], mut pos: usize) -> Result<(String, usize)> {
+    let invalid = || eyre!("invalid name in DNS response");
+    let mut labels = Vec::new();
+    let mut end = None;
+    for _ in 0..DNS_MAX_POINTERS {
+        loop {
+            let len = usize::from(*msg.get(pos).ok_or_else(invalid)?);
+            match len {
+                0 => {
+                    return Ok((labels.join("."), end.unwrap_or(pos + 1)));
+                }
+                len if len & 0xc0 == 0xc0 => {
+                    let low = usize::from(*msg.get(pos + 1).ok_or_else(invalid)?);
+                    end.get_or_insert(pos + 2);
+                    pos = ((len & 0x3f) << 8) | low;
+                    break;
+                }
+                len => {
+                    let label = msg.get(pos + 1..pos + 1 + len).ok_or_else(invalid)?;
+                    labels.push(String::from_utf8_lossy(label).to_string());
+                    pos += 1 + len;
+                }
+            }
+        }
+    }
+    Err(invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// Serve one connection with `response` after reading the request, returns the port.
+    fn serve_once(response: &'static [u8]) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf);
+            stream.write_all(response).unwrap();
+        });
+        port
+    }
+
+    #[test]
+    fn test_tcp_request() {
+        let port = serve_once(b"+PONG\r\n");
+        let response = tcp_request("127.0.0.1", port, b"PING\r\n", Duration::from_secs(2), 1024, |data| {
+            Ok(data.ends_with(b"\r\n"))
+        }).unwrap();
+        assert_eq!(response, b"+PONG\r\n");
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let err = tcp_request("127.0.0.1", port, b"PING\r\n", Duration::from_millis(50), 1024, |_| Ok(false))
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out after 50 ms"), "{err}");
+    }
+
+    #[test]
+    fn test_http_get() {
+        let port = serve_once(b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n\
+            4\r\n[{\"a\r\n6\r\n\": 1}]\r\n0\r\n\r\n");
+        let url = format!("http://127.0.0.1:{port}/v1/catalog/service/db?dc=dc1");
+        let response = http_get(&url, &[], Duration::from_secs(2), 1024).unwrap();
+        assert_eq!(response.status, 200);
+        assert!(response.headers.contains(&("content-type".to_string(), "application/json".to_string())));
+        assert_eq!(response.body, b"[{\"a\": 1}]");
+
+        assert!(http_get("https://example.com", &[], Duration::from_secs(1), 1024).unwrap_err()
+            .to_string().contains("https is not supported"));
+        assert_eq!(split_host_port("[::1]:8500", 80).unwrap(), ("::1".to_string(), 8500));
+        assert_eq!(split_host_port("consul", 80).unwrap(), ("consul".to_string(), 80));
+    }
+
+    #[test]
+    fn test_dns_srv_messages() {
+        let query = dns_query(0x1234, "_mongodb._tcp.example.com.", DNS_TYPE_SRV).unwrap();
+        assert_eq!(&query[..4], &[0x12, 0x34, 0x01, 0x00]);
+        assert_eq!(&query[12..21], b"\x08_mongodb");
+        assert!(dns_query(1, "a..b", DNS_TYPE_SRV).is_err());
+
+        // the answers point back to the question name and share the `example.com` suffix
+        let mut response = query.clone();
+        response[2] = 0x81;
+        response[3] = 0x80;
+        response[7] = 2;
+        for (priority, weight, port, target) in [(10u16, 5u16, 27017u16, &b"\x02db"[..]), (0, 1, 27018, b"\x03db2")] {
+            response.extend_from_slice(&[0xc0, 0x0c]);
+            response.extend_from_slice(&DNS_TYPE_SRV.to_be_bytes());
+            response.extend_from_slice(&[0, 1, 0, 0, 0, 60]);
+            response.extend_from_slice(&(6 + target.len() as u16 + 2).to_be_bytes());
+            for value in [priority, weight, port] {
+                response.extend_from_slice(&value.to_be_bytes());
+            }
+            response.extend_from_slice(target);
+            response.extend_from_slice(&[0xc0, 0x1a]);
+        }
+
+        let records = parse_srv_response(0x1234, &response).unwrap();
+        assert_eq!(records[0], SrvRecord { target: "db.example.com".to_string(), port: 27017, priority: 10, weight: 5 });
+        assert_eq!(records[1].target, "db2.example.com");
+        assert!(parse_srv_response(0x4321, &response).is_err());
+
+        response[3] = 0x83;
+        assert!(parse_srv_response(0x1234, &response).unwrap().is_empty());
+        assert_eq!(parse_nameserver("10.0.0.2").unwrap(), "10.0.0.2:53".parse().unwrap());
+    }
+}
@@ -8,6 +8,10 @@ pub struct Configs;
 #[folder = "scripts/"]
 pub struct Scripts;
 
+#[derive(RustEmbed)]
+#[folder = "lib/"]
+pub struct Libs;
+
 #[allow(unused)]
 pub fn debug_embed() {
     println!("Configs:");
@@ -19,4 +23,9 @@ pub fn debug_embed() {
     for file in Scripts::iter() {
         println!("  {file}");
     }
+
+    println!("Libs:");
+    for file in Libs::iter() {
+        println!("  {file}");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,123 @@
+//! JetBrains DataGrip `dataSources.xml` and `dataSources.local.xml`.
+
+use super::{db_type_for_driver, fill_from_jdbc_url, new_database, sanitize_alias, ImportOptions, Imported};
+use color_eyre::eyre::{eyre, Result};
+use std::collections::HashMap;
+
+pub(super) fn parse(content: &str, options: &ImportOptions, imported: &mut Imported) -> Result<()> {
+    let document = roxmltree::Document::parse(content)
+        .map_err(|e| eyre!("Invalid DataGrip dataSources.xml: {}", e))?;
+
+    for source in document.descendants().filter(|n| n.has_tag_name("data-source")) {
+        let child_text = |tag: &str| -> String {
+            source
+                .children()
+                .find(|n| n.has_tag_name(tag))
+                .and_then(|n| n.text())
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        };
+
+        let name = source.attribute("name").unwrap_or_default().to_string();
+        let driver = child_text("driver-ref");
+        let jdbc_driver = child_text("jdbc-driver");
+
+        let Some(db_type) = db_type_for_driver(&driver).or_else(|| db_type_for_driver(&jdbc_driver)) else {
+            imported.warnings.push(format!("{name}: unsupported DataGrip driver `{driver}`, skipped"));
+            continue;
+        };
+
+        let mut vars = HashMap::from([
+            ("user".to_string(), child_text("user-name")),
+            ("password".to_string(), String::new()),
+        ]);
+        let url = child_text("jdbc-url");
+        fill_from_jdbc_url(&url, db_type, &mut vars);
+        if matches!(db_type, "sqlite" | "duckdb") {
+            // e.g. jdbc:sqlite:/path/to/app.db
+            let path = url.split_once(&format!("{db_type}:")).map(|(_, p)| p).unwrap_or_default();
+            vars.insert("path".to_string(), path.to_string());
+        } else {
+            imported.warnings.push(format!(
+                "{name}: DataGrip keeps passwords in the system keychain, set it manually"
+            ));
+        }
+
+        let mut annotations = HashMap::from([("datagrip/driver".to_string(), driver.clone())]);
+        let folder = source.attribute("group").unwrap_or_default();
+        if !folder.is_empty() {
+            annotations.insert("datagrip/folder".to_string(), folder.to_string());
+        }
+        let properties = source
+            .children()
+            .filter(|n| n.has_tag_name("driver-properties"))
+            .flat_map(|n| n.children())
+            .filter(|n| n.has_tag_name("property"));
+        for property in properties {
+            if let (Some(key), Some(value)) = (property.attribute("name"), property.attribute("value")) {
+                annotations.insert(format!("datagrip/property/{key}"), value.to_string());
+            }
+        }
+
+        // Top level folders are environments, e.g. `prod/eu` goes to `prod`.
+        let mut options = options.clone();
+        if let Some(env) = folder.split('/').map(sanitize_alias).find(|env| !env.is_empty()) {
+            options.env = env;
+        }
+
+        imported.databases.push(new_database(db_type, &name, &options, vars, annotations));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_datagrip() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<project version="4">
+  <component name="DataSourceManagerImpl" format="xml" multifile-model="true">
+    <data-source source="LOCAL" name="billing@prod" uuid="1" group="Prod/Billing">
+      <driver-ref>postgresql</driver-ref>
+      <jdbc-driver>org.postgresql.Driver</jdbc-driver>
+      <jdbc-url>jdbc:postgresql://billing.internal:5432/billing</jdbc-url>
+      <user-name>billing_ro</user-name>
+      <driver-properties>
+        <property name="sslmode" value="require" />
+      </driver-properties>
+    </data-source>
+    <data-source source="LOCAL" name="scratch" uuid="2">
+      <driver-ref>sqlite.xerial</driver-ref>
+      <jdbc-url>jdbc:sqlite:/tmp/scratch.db</jdbc-url>
+    </data-source>
+    <data-source source="LOCAL" name="lake" uuid="3">
+      <driver-ref>snowflake</driver-ref>
+    </data-source>
+  </component>
+</project>"#;
+        let mut imported = Imported::default();
+        parse(content, &ImportOptions::default(), &mut imported).unwrap();
+
+        assert_eq!(imported.databases.len(), 2);
+        let billing = &imported.databases[0];
+        assert_eq!(billing.alias, "billing-prod");
+        assert_eq!(billing.db_type, "postgres");
+        assert_eq!(billing.env, "prod");
+        let vars = billing.vars.as_ref().unwrap();
+        assert_eq!(vars["host"], "billing.internal");
+        assert_eq!(vars["user"], "billing_ro");
+        let annotations = billing.annotations.as_ref().unwrap();
+        assert_eq!(annotations["datagrip/folder"], "Prod/Billing");
+        assert_eq!(annotations["datagrip/property/sslmode"], "require");
+
+        let scratch = &imported.databases[1];
+        assert_eq!(scratch.db_type, "sqlite");
+        assert_eq!(scratch.vars.as_ref().unwrap()["path"], "/tmp/scratch.db");
+
+        assert_eq!(imported.warnings.len(), 2);
+    }
+}
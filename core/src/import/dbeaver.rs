@@ -0,0 +1,138 @@
+//! DBeaver workspace `data-sources.json`.
+
+use super::{db_type_for_driver, fill_from_jdbc_url, new_database, sanitize_alias, ImportOptions, Imported};
+use color_eyre::eyre::{eyre, Result};
+use std::collections::HashMap;
+
+pub(super) fn parse(content: &str, options: &ImportOptions, imported: &mut Imported) -> Result<()> {
+    let root: serde_json::Value = serde_json::from_str(content)
+        .map_err(|e| eyre!("Invalid DBeaver data-sources.json: {}", e))?;
+
+    let Some(connections) = root.get("connections").and_then(|c| c.as_object()) else {
+        return Ok(());
+    };
+
+    for (id, connection) in connections {
+        let text = |value: &serde_json::Value, key: &str| -> String {
+            value.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string()
+        };
+
+        let name = match text(connection, "name") {
+            name if name.is_empty() => id.clone(),
+            name => name,
+        };
+        let provider = text(connection, "provider");
+        let driver = text(connection, "driver");
+
+        let Some(db_type) = db_type_for_driver(&driver).or_else(|| db_type_for_driver(&provider)) else {
+            imported.warnings.push(format!("{name}: unsupported DBeaver driver `{provider}/{driver}`, skipped"));
+            continue;
+        };
+
+        let empty = serde_json::Value::Null;
+        let configuration = connection.get("configuration").unwrap_or(&empty);
+
+        let mut vars = HashMap::from([
+            ("host".to_string(), text(configuration, "host")),
+            ("port".to_string(), text(configuration, "port")),
+            ("database".to_string(), text(configuration, "database")),
+            ("user".to_string(), text(configuration, "user")),
+            ("password".to_string(), text(configuration, "password")),
+            ("query".to_string(), String::new()),
+        ]);
+        fill_from_jdbc_url(&text(configuration, "url"), db_type, &mut vars);
+        if matches!(db_type, "sqlite" | "duckdb") {
+            vars.insert("path".to_string(), vars["database"].clone());
+        }
+
+        let saves_password = connection.get("save-password").and_then(|v| v.as_bool()).unwrap_or(false);
+        if saves_password && vars["password"].is_empty() {
+            imported.warnings.push(format!(
+                "{name}: credentials are stored encrypted by DBeaver (credentials-config.json), set them manually"
+            ));
+        }
+
+        let mut annotations = HashMap::from([("dbeaver/driver".to_string(), format!("{provider}/{driver}"))]);
+        let folder = text(connection, "folder");
+        if !folder.is_empty() {
+            annotations.insert("dbeaver/folder".to_string(), folder.clone());
+        }
+        if let Some(properties) = configuration.get("properties").and_then(|p| p.as_object()) {
+            for (key, value) in properties {
+                let value = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+                annotations.insert(format!("dbeaver/property/{key}"), value);
+            }
+        }
+
+        // Top level folders are environments, e.g. `prod/eu` goes to `prod`.
+        let mut options = options.clone();
+        if let Some(env) = folder.split('/').map(sanitize_alias).find(|env| !env.is_empty()) {
+            options.env = env;
+        }
+
+        imported.databases.push(new_database(db_type, &name, &options, vars, annotations));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dbeaver() {
+        let content = r#"{
+  "folders": { "Prod": {}, "Prod/EU": { "parent": "Prod" } },
+  "connections": {
+    "mysql8-1": {
+      "provider": "mysql",
+      "driver": "mysql8",
+      "name": "Orders EU",
+      "folder": "Prod/EU",
+      "save-password": true,
+      "configuration": {
+        "host": "orders.eu.internal",
+        "port": "3306",
+        "database": "orders",
+        "url": "jdbc:mysql://orders.eu.internal:3306/orders",
+        "properties": { "useSSL": "true" }
+      }
+    },
+    "postgres-jdbc-2": {
+      "provider": "postgresql",
+      "driver": "postgres-jdbc",
+      "name": "local pg",
+      "configuration": {
+        "url": "jdbc:postgresql://localhost:5433/app?sslmode=disable",
+        "user": "postgres",
+        "password": "postgres"
+      }
+    },
+    "snowflake-3": { "provider": "generic", "driver": "snowflake", "name": "warehouse" }
+  }
+}"#;
+        let mut imported = Imported::default();
+        parse(content, &ImportOptions::default(), &mut imported).unwrap();
+
+        assert_eq!(imported.databases.len(), 2);
+        let orders = imported.databases.iter().find(|db| db.alias == "orders-eu").unwrap();
+        assert_eq!(orders.db_type, "mysql");
+        assert_eq!(orders.env, "prod");
+        let annotations = orders.annotations.as_ref().unwrap();
+        assert_eq!(annotations["dbeaver/folder"], "Prod/EU");
+        assert_eq!(annotations["dbeaver/property/useSSL"], "true");
+
+        let local = imported.databases.iter().find(|db| db.alias == "local-pg").unwrap();
+        assert_eq!(local.env, "local");
+        let vars = local.vars.as_ref().unwrap();
+        assert_eq!(vars["host"], "localhost");
+        assert_eq!(vars["port"], "5433");
+        assert_eq!(vars["database"], "app");
+        assert_eq!(vars["query"], "sslmode=disable");
+        assert_eq!(vars["password"], "postgres");
+
+        // Encrypted credentials and the unsupported driver are reported.
+        assert_eq!(imported.warnings.len(), 2);
+    }
+}
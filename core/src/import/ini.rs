@@ -0,0 +1,96 @@
+//! Minimal INI parser for MySQL option files and `pg_service.conf`.
+
+/// A section of an INI file, keys keep the order of the file.
+#[derive(Debug, Default)]
+pub(super) struct Section {
+    pub name: String,
+    pub entries: Vec<(String, String)>,
+}
+
+impl Section {
+    /// Get the last value of a key, later entries override earlier ones.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Parse INI content into sections.
+///
+/// Supports `#` and `;` comments, `key = value`, `key=value` and bare `key` entries.
+/// Values wrapped in single or double quotes are unquoted.
+/// Entries before the first section and `!include` directives are ignored.
+pub(super) fn parse(content: &str) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') || line.starts_with('!') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push(Section {
+                name: name.trim().to_string(),
+                entries: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some(section) = sections.last_mut() else {
+            continue;
+        };
+
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), unquote(value.trim())),
+            None => (line, ""),
+        };
+        // MySQL treats `-` and `_` in option names the same.
+        section.entries.push((key.replace('-', "_"), value.to_string()));
+    }
+
+    sections
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return &value[1..value.len() - 1];
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ini() {
+        let content = r#"
+# comment
+orphan = 1
+[client]
+user = "root"
+password='p=ss'
+ssl-ca = /etc/ca.pem
+skip-ssl
+!includedir /etc/mysql/conf.d/
+
+[mysql]
+; comment
+database=test
+"#;
+        let sections = parse(content);
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].name, "client");
+        assert_eq!(sections[0].get("user"), Some("root"));
+        assert_eq!(sections[0].get("password"), Some("p=ss"));
+        assert_eq!(sections[0].get("ssl_ca"), Some("/etc/ca.pem"));
+        assert_eq!(sections[0].get("skip_ssl"), Some(""));
+        assert_eq!(sections[1].get("database"), Some("test"));
+    }
+}
@@ -0,0 +1,369 @@
+//! Import connections from other tools' config files.
+
+mod datagrip;
+mod dbeaver;
+mod ini;
+mod mycnf;
+mod pgpass;
+mod uri;
+
+use crate::config::{Config, Database};
+use color_eyre::eyre::{eyre, Result};
+use std::{
+    collections::{HashMap, HashSet},
+    path,
+};
+
+/// Supported import formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// MySQL option files (`~/.my.cnf`) or `mysql_config_editor print --all` output.
+    MyCnf,
+    /// PostgreSQL password file (`~/.pgpass`).
+    PgPass,
+    /// PostgreSQL connection service file (`pg_service.conf`).
+    PgService,
+    /// Files containing `mongodb://` or `mongodb+srv://` URIs.
+    Mongo,
+    /// Files containing `redis://` or `rediss://` URLs.
+    Redis,
+    /// DBeaver workspace `data-sources.json`.
+    Dbeaver,
+    /// JetBrains DataGrip `dataSources.xml` (or `dataSources.local.xml`).
+    Datagrip,
+}
+
+impl std::str::FromStr for ImportFormat {
+    type Err = color_eyre::eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "mycnf" | "my.cnf" => Ok(ImportFormat::MyCnf),
+            "pgpass" => Ok(ImportFormat::PgPass),
+            "pg-service" | "pg_service" => Ok(ImportFormat::PgService),
+            "mongo" | "mongodb" => Ok(ImportFormat::Mongo),
+            "redis" => Ok(ImportFormat::Redis),
+            "dbeaver" => Ok(ImportFormat::Dbeaver),
+            "datagrip" => Ok(ImportFormat::Datagrip),
+            _ => Err(eyre!(
+                "Unsupported import format: {} \
+                 (expected mycnf, pgpass, pg-service, mongo, redis, dbeaver or datagrip)",
+                s
+            )),
+        }
+    }
+}
+
+/// Options for importing connections.
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Environment assigned to imported databases.
+    pub env: String,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        ImportOptions { env: "local".to_string() }
+    }
+}
+
+/// Result of an import.
+#[derive(Debug, Default)]
+pub struct Imported {
+    /// Databases ready to be written to a config file.
+    pub databases: Vec<Database>,
+    /// Entries that were skipped or only partially imported, with the reason.
+    pub warnings: Vec<String>,
+    /// Aliases that already exist, these databases are not imported.
+    pub conflicts: Vec<String>,
+}
+
+/// Parse connections from the content of a file in the given format.
+///
+/// Databases only carry `vars` at this point, see `import_file` for rendering DSNs.
+pub fn parse(format: ImportFormat, content: &str, options: &ImportOptions) -> Result<Imported> {
+    let mut imported = Imported::default();
+    match format {
+        ImportFormat::MyCnf => mycnf::parse(content, options, &mut imported),
+        ImportFormat::PgPass => pgpass::parse_pgpass(content, options, &mut imported),
+        ImportFormat::PgService => pgpass::parse_pg_service(content, options, &mut imported),
+        ImportFormat::Mongo => uri::parse(content, "mongo", options, &mut imported),
+        ImportFormat::Redis => uri::parse(content, "redis", options, &mut imported),
+        ImportFormat::Dbeaver => dbeaver::parse(content, options, &mut imported)?,
+        ImportFormat::Datagrip => datagrip::parse(content, options, &mut imported)?,
+    }
+    Ok(imported)
+}
+
+/// Import connections from a file.
+///
+/// The DSN of each database is rendered with the configured template of its type,
+/// databases without a template keep their variables in `vars`.
+/// Databases whose alias already exists in `cfg` are reported as conflicts.
+pub fn import_file(
+    format: ImportFormat,
+    file: &path::Path,
+    cfg: &Config,
+    options: &ImportOptions,
+) -> Result<Imported> {
+    let content = std::fs::read_to_string(file)
+        .map_err(|e| eyre!("Failed to read '{}': {}", file.display(), e))?;
+
+    let mut imported = parse(format, &content, options)?;
+    render_dsn(&mut imported, cfg);
+    resolve_conflicts(&mut imported, cfg);
+    Ok(imported)
+}
+
+/// Render DSNs from the configured templates.
+pub(crate) fn render_dsn(imported: &mut Imported, cfg: &Config) {
+    for db in imported.databases.iter_mut() {
+        let Ok(template) = cfg.get_template(&db.db_type) else {
+            imported.warnings.push(format!(
+                "{}: no template for database type `{}`, variables are kept in `vars`",
+                db.alias, db.db_type
+            ));
+            continue;
+        };
+
+        let vars = db.vars.take().unwrap_or_default();
+        db.dsn = crate::template::fill_template(&template.dsn, &vars);
+    }
+}
+
+/// Move databases whose alias already exists, in the config or earlier in the import, to conflicts.
+pub(crate) fn resolve_conflicts(imported: &mut Imported, cfg: &Config) {
+    let mut seen: HashSet<String> = cfg.aliases.keys().cloned().collect();
+    let databases = std::mem::take(&mut imported.databases);
+
+    for db in databases {
+        if seen.insert(db.alias.clone()) {
+            imported.databases.push(db);
+        } else {
+            imported.conflicts.push(db.alias);
+        }
+    }
+}
+
+/// Build an imported database with the given variables.
+pub(crate) fn new_database(
+    db_type: &str,
+    alias: &str,
+    options: &ImportOptions,
+    vars: HashMap<String, String>,
+    annotations: HashMap<String, String>,
+) -> Database {
+    Database {
+        db_type: db_type.to_string(),
+        dsn: None,
+        vars: Some(vars),
+        env: options.env.clone(),
+        alias: sanitize_alias(alias),
+        description: None,
+        annotations: if annotations.is_empty() { None } else { Some(annotations) },
+        script: None,
+    }
+}
+
+/// Map a driver or provider name of a GUI client to a dbhub database type.
+///
+/// The longest known name contained in the driver wins, so forks like `starrocks-mysql`
+/// are not taken for the database they are compatible with.
+pub(crate) fn db_type_for_driver(driver: &str) -> Option<&'static str> {
+    const DRIVERS: &[(&str, &str)] = &[
+        ("mysql", "mysql"),
+        ("mariadb", "mysql"),
+        ("postgres", "postgres"),
+        ("mongo", "mongo"),
+        ("redis", "redis"),
+        ("sqlite", "sqlite"),
+        ("duckdb", "duckdb"),
+        ("clickhouse", "clickhouse"),
+        ("doris", "doris"),
+        ("starrocks", "doris"),
+        ("sqlserver", "mssql"),
+        ("mssql", "mssql"),
+        ("oracle", "oracle"),
+        ("cassandra", "cassandra"),
+    ];
+
+    let driver = driver.to_lowercase();
+    DRIVERS
+        .iter()
+        .filter(|(name, _)| driver.contains(name))
+        .max_by_key(|(name, _)| name.len())
+        .map(|(_, db_type)| *db_type)
+}
+
+/// Fill connection variables from a JDBC URL, only for variables not set yet.
+///
+/// e.g. `jdbc:postgresql://db:5432/app?sslmode=require`,
+/// `jdbc:sqlserver://db:1433;databaseName=app;encrypt=true` or `jdbc:oracle:thin:@//db:1521/svc`.
+/// The database becomes the `service` of oracle and the `keyspace` of cassandra templates.
+pub(crate) fn fill_from_jdbc_url(url: &str, db_type: &str, vars: &mut HashMap<String, String>) {
+    let fill = |vars: &mut HashMap<String, String>, key: &str, value: &str| {
+        let current = vars.entry(key.to_string()).or_default();
+        if current.is_empty() {
+            *current = value.to_string();
+        }
+    };
+
+    if let Some(uri) = parse_jdbc_url(url) {
+        let (host, port) = uri.first_host();
+        fill(vars, "host", host);
+        fill(vars, "port", port.unwrap_or_default());
+        fill(vars, "database", &uri.path);
+        fill(vars, "query", &uri.query);
+    }
+
+    let database = vars.get("database").cloned().unwrap_or_default();
+    match db_type {
+        "oracle" => fill(vars, "service", &database),
+        "cassandra" => fill(vars, "keyspace", &database),
+        _ => {}
+    }
+}
+
+/// Parse the URL forms of JDBC drivers into URI components.
+fn parse_jdbc_url(url: &str) -> Option<uri::Uri> {
+    let url = url.strip_prefix("jdbc:")?;
+
+    // oracle: `oracle:thin:@//host:port/service` or `oracle:thin:@host:port:SID`
+    if let Some((_, address)) = url.strip_prefix("oracle:").and_then(|rest| rest.split_once('@')) {
+        if let Some(address) = address.strip_prefix("//") {
+            return uri::Uri::parse(&format!("oracle://{address}"));
+        }
+        let mut parts = address.splitn(3, ':');
+        let (host, port, sid) = (parts.next()?, parts.next()?, parts.next()?);
+        if host.starts_with('(') {
+            return None;
+        }
+        return uri::Uri::parse(&format!("oracle://{host}:{port}/{sid}"));
+    }
+
+    // sqlserver: `sqlserver://host:port;databaseName=app;encrypt=true`
+    if let Some(rest) = url.strip_prefix("sqlserver://") {
+        let mut parts = rest.split(';');
+        let hosts = parts.next().unwrap_or_default();
+        let mut database = String::new();
+        let mut query = Vec::new();
+        for (key, value) in parts.filter_map(|part| part.split_once('=')) {
+            if key.eq_ignore_ascii_case("databaseName") || key.eq_ignore_ascii_case("database") {
+                database = value.to_string();
+            } else {
+                query.push(format!("{key}={value}"));
+            }
+        }
+        let mut uri = uri::Uri::parse(&format!("sqlserver://{hosts}"))?;
+        uri.path = database;
+        uri.query = query.join("&");
+        return Some(uri);
+    }
+
+    uri::Uri::parse(url)
+}
+
+/// Make an alias out of arbitrary names: lowercase, only `[a-z0-9._-]`.
+pub(crate) fn sanitize_alias(alias: &str) -> String {
+    let alias: String = alias
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') { c } else { '-' })
+        .collect();
+
+    alias
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_alias() {
+        assert_eq!(sanitize_alias("Prod / Orders DB"), "prod-orders-db");
+        assert_eq!(sanitize_alias("pg-db.internal-app_1"), "pg-db.internal-app_1");
+    }
+
+    #[test]
+    fn test_db_type_for_driver() {
+        let drivers = [
+            ("mysql8", "mysql"),
+            ("MariaDB", "mysql"),
+            ("postgres-jdbc", "postgres"),
+            ("mongodb", "mongo"),
+            ("redis", "redis"),
+            ("sqlite.xerial", "sqlite"),
+            ("duckdb_jdbc", "duckdb"),
+            ("com_clickhouse", "clickhouse"),
+            ("doris", "doris"),
+            ("starrocks", "doris"),
+            ("sqlserver", "mssql"),
+            ("mssql_jdbc_ms_new", "mssql"),
+            ("oracle_thin", "oracle"),
+            ("cassandra", "cassandra"),
+        ];
+        for (driver, db_type) in drivers {
+            assert_eq!(db_type_for_driver(driver), Some(db_type), "{driver}");
+        }
+        assert_eq!(db_type_for_driver("snowflake"), None);
+
+        // names containing several known drivers go to the most specific one
+        assert_eq!(db_type_for_driver("starrocks-mysql"), Some("doris"));
+        assert_eq!(db_type_for_driver("clickhouse_mysql_compat"), Some("clickhouse"));
+        assert_eq!(db_type_for_driver("mariadb-mysql"), Some("mysql"));
+    }
+
+    #[test]
+    fn test_fill_from_jdbc_url() {
+        let fill = |url: &str, db_type: &str| {
+            let mut vars = HashMap::new();
+            fill_from_jdbc_url(url, db_type, &mut vars);
+            vars
+        };
+
+        let vars = fill("jdbc:postgresql://db:5432/app?sslmode=require", "postgres");
+        assert_eq!((vars["host"].as_str(), vars["port"].as_str(), vars["database"].as_str()), ("db", "5432", "app"));
+        assert_eq!(vars["query"], "sslmode=require");
+
+        let vars = fill("jdbc:sqlserver://sql.local:1433;databaseName=sales;encrypt=true", "mssql");
+        assert_eq!((vars["host"].as_str(), vars["port"].as_str()), ("sql.local", "1433"));
+        assert_eq!((vars["database"].as_str(), vars["query"].as_str()), ("sales", "encrypt=true"));
+
+        let vars = fill("jdbc:oracle:thin:@//ora.local:1521/FREEPDB1", "oracle");
+        assert_eq!((vars["host"].as_str(), vars["port"].as_str()), ("ora.local", "1521"));
+        assert_eq!(vars["service"], "FREEPDB1");
+        assert_eq!(fill("jdbc:oracle:thin:@ora.local:1521:ORCL", "oracle")["service"], "ORCL");
+
+        let vars = fill("jdbc:cassandra://cass.local:9042/events", "cassandra");
+        assert_eq!((vars["host"].as_str(), vars["keyspace"].as_str()), ("cass.local", "events"));
+
+        // variables that are already set are kept
+        let mut vars = HashMap::from([("host".to_string(), "primary".to_string())]);
+        fill_from_jdbc_url("jdbc:mysql://replica:3306/app", "mysql", &mut vars);
+        assert_eq!((vars["host"].as_str(), vars["database"].as_str()), ("primary", "app"));
+    }
+
+    #[test]
+    fn test_resolve_conflicts() {
+        let mut cfg: Config = serde_yaml::from_str("databases: []").unwrap();
+        cfg.aliases.insert("mysql-client".to_string(), 0);
+
+        let options = ImportOptions::default();
+        let mut imported = Imported {
+            databases: vec![
+                new_database("mysql", "mysql-client", &options, HashMap::new(), HashMap::new()),
+                new_database("mysql", "mysql-app", &options, HashMap::new(), HashMap::new()),
+                new_database("mysql", "mysql-app", &options, HashMap::new(), HashMap::new()),
+            ],
+            ..Default::default()
+        };
+
+        resolve_conflicts(&mut imported, &cfg);
+        assert_eq!(imported.databases.len(), 1);
+        assert_eq!(imported.conflicts, vec!["mysql-client", "mysql-app"]);
+    }
+}
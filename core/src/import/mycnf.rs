@@ -0,0 +1,127 @@
+//! MySQL option files: `~/.my.cnf` and `mysql_config_editor print --all` output.
+
+use super::{ini, new_database, ImportOptions, Imported};
+use std::collections::HashMap;
+
+/// Option groups read by servers and tools, they do not describe a connection.
+const SKIPPED_GROUPS: &[&str] = &[
+    "mysqld", "server", "embedded", "mysqldump", "mysqladmin", "mysqlimport",
+    "mysqlcheck", "mysqlshow", "mysqlslap", "mysqlbinlog", "mysql_upgrade",
+];
+
+/// Groups that apply to the `mysql` client, they are merged into one connection.
+const CLIENT_GROUPS: &[&str] = &["client", "mysql"];
+
+/// Masked password printed by `mysql_config_editor print`.
+const MASKED_PASSWORD: &str = "*****";
+
+pub(super) fn parse(content: &str, options: &ImportOptions, imported: &mut Imported) {
+    let mut client = ini::Section {
+        name: "client".to_string(),
+        entries: Vec::new(),
+    };
+    let mut groups = Vec::new();
+
+    for section in ini::parse(content) {
+        if SKIPPED_GROUPS.contains(&section.name.as_str()) {
+            continue;
+        }
+        if CLIENT_GROUPS.contains(&section.name.as_str()) {
+            client.entries.extend(section.entries);
+        } else {
+            groups.push(section);
+        }
+    }
+    groups.insert(0, client);
+
+    for group in groups {
+        if !["host", "user", "socket", "port"].iter().any(|k| group.get(k).is_some()) {
+            continue;
+        }
+
+        let alias = format!("mysql-{}", group.name);
+        let mut vars = HashMap::from([
+            ("host".to_string(), group.get("host").unwrap_or("localhost").to_string()),
+            ("port".to_string(), group.get("port").unwrap_or("3306").to_string()),
+            ("user".to_string(), group.get("user").unwrap_or_default().to_string()),
+            ("database".to_string(), group.get("database").unwrap_or_default().to_string()),
+            ("query".to_string(), String::new()),
+        ]);
+
+        match group.get("password") {
+            Some(MASKED_PASSWORD) => imported.warnings.push(format!(
+                "{alias}: password is masked by mysql_config_editor, set it manually"
+            )),
+            Some(password) => {
+                vars.insert("password".to_string(), password.to_string());
+            }
+            None => {}
+        }
+
+        let mut annotations = HashMap::new();
+        for (key, value) in &group.entries {
+            if key == "socket" || key.starts_with("ssl_") {
+                annotations.insert(format!("mysql/{}", key.replace('_', "-")), value.clone());
+            }
+        }
+
+        imported.databases.push(new_database("mysql", &alias, options, vars, annotations));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_my_cnf() {
+        let content = r#"
+[client]
+user=app
+password="s3cr:t"
+host=db.local
+ssl-ca=/etc/ca.pem
+
+[mysql]
+database=orders
+
+[mysqld]
+port=3307
+
+[mysqldump]
+user=backup
+
+[server_x]
+host=x.local
+
+[mysqldump_prod]
+host=prod.local
+
+[reporting]
+user = "report"
+password = *****
+host = "replica.local"
+port = 3307
+"#;
+        let mut imported = Imported::default();
+        parse(content, &ImportOptions::default(), &mut imported);
+
+        let aliases: Vec<_> = imported.databases.iter().map(|db| db.alias.as_str()).collect();
+        assert_eq!(aliases, vec!["mysql-client", "mysql-server_x", "mysql-mysqldump_prod", "mysql-reporting"]);
+        let client = &imported.databases[0];
+        assert_eq!(client.alias, "mysql-client");
+        let vars = client.vars.as_ref().unwrap();
+        assert_eq!(vars["user"], "app");
+        assert_eq!(vars["password"], "s3cr:t");
+        assert_eq!(vars["host"], "db.local");
+        assert_eq!(vars["port"], "3306");
+        assert_eq!(vars["database"], "orders");
+        assert_eq!(client.annotations.as_ref().unwrap()["mysql/ssl-ca"], "/etc/ca.pem");
+
+        let reporting = &imported.databases[3];
+        assert_eq!(reporting.alias, "mysql-reporting");
+        assert_eq!(reporting.vars.as_ref().unwrap()["port"], "3307");
+        assert!(!reporting.vars.as_ref().unwrap().contains_key("password"));
+        assert_eq!(imported.warnings.len(), 1);
+    }
+}
@@ -0,0 +1,152 @@
+//! PostgreSQL password file (`~/.pgpass`) and connection service file (`pg_service.conf`).
+
+use super::{ini, new_database, ImportOptions, Imported};
+use std::collections::HashMap;
+
+const DEFAULT_PORT: &str = "5432";
+const WILDCARD: &str = "*";
+
+/// Parse `hostname:port:database:username:password` lines.
+pub(super) fn parse_pgpass(content: &str, options: &ImportOptions, imported: &mut Imported) {
+    for (lineno, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields = split_pgpass_line(line);
+        let [host, port, database, user, password] = fields.as_slice() else {
+            imported.warnings.push(format!("pgpass line {}: expected 5 fields, skipped", lineno + 1));
+            continue;
+        };
+
+        if host == WILDCARD || user == WILDCARD {
+            imported.warnings.push(format!(
+                "pgpass line {}: wildcard host or user cannot be imported, skipped",
+                lineno + 1
+            ));
+            continue;
+        }
+
+        let database = if database == WILDCARD { "" } else { database.as_str() };
+        let port = if port == WILDCARD { DEFAULT_PORT } else { port.as_str() };
+
+        let alias = match database {
+            "" => format!("pg-{host}-{user}"),
+            database => format!("pg-{host}-{database}"),
+        };
+        let vars = HashMap::from([
+            ("host".to_string(), host.clone()),
+            ("port".to_string(), port.to_string()),
+            ("database".to_string(), database.to_string()),
+            ("user".to_string(), user.clone()),
+            ("password".to_string(), password.clone()),
+            ("query".to_string(), String::new()),
+        ]);
+
+        imported.databases.push(new_database("postgres", &alias, options, vars, HashMap::new()));
+    }
+}
+
+/// Split a pgpass line on `:`, `\:` and `\\` are escapes for `:` and `\`.
+fn split_pgpass_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    fields.last_mut().unwrap().push(escaped);
+                }
+            }
+            ':' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+
+    fields
+}
+
+/// Parse `pg_service.conf`, one connection per service section.
+///
+/// Connection parameters other than host, port, dbname, user and password
+/// (e.g. `sslmode`) are kept in the `query` variable.
+pub(super) fn parse_pg_service(content: &str, options: &ImportOptions, imported: &mut Imported) {
+    for service in ini::parse(content) {
+        let host = service.get("host").or(service.get("hostaddr")).unwrap_or("localhost");
+
+        let query: Vec<String> = service.entries
+            .iter()
+            .filter(|(k, _)| !["host", "hostaddr", "port", "dbname", "user", "password"].contains(&k.as_str()))
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect();
+
+        let vars = HashMap::from([
+            ("host".to_string(), host.to_string()),
+            ("port".to_string(), service.get("port").unwrap_or(DEFAULT_PORT).to_string()),
+            ("database".to_string(), service.get("dbname").unwrap_or_default().to_string()),
+            ("user".to_string(), service.get("user").unwrap_or_default().to_string()),
+            ("password".to_string(), service.get("password").unwrap_or_default().to_string()),
+            ("query".to_string(), query.join("&")),
+        ]);
+
+        let alias = format!("pg-{}", service.name);
+        imported.databases.push(new_database("postgres", &alias, options, vars, HashMap::new()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pgpass() {
+        let content = r#"
+# hostname:port:database:username:password
+db.prod:5433:orders:app:pa\:ss\\word
+localhost:*:*:postgres:postgres
+*:*:*:readonly:secret
+broken:line
+"#;
+        let mut imported = Imported::default();
+        parse_pgpass(content, &ImportOptions::default(), &mut imported);
+
+        assert_eq!(imported.databases.len(), 2);
+        let orders = &imported.databases[0];
+        assert_eq!(orders.alias, "pg-db.prod-orders");
+        assert_eq!(orders.db_type, "postgres");
+        let vars = orders.vars.as_ref().unwrap();
+        assert_eq!(vars["port"], "5433");
+        assert_eq!(vars["password"], "pa:ss\\word");
+
+        let local = &imported.databases[1];
+        assert_eq!(local.alias, "pg-localhost-postgres");
+        assert_eq!(local.vars.as_ref().unwrap()["port"], "5432");
+        assert_eq!(local.vars.as_ref().unwrap()["database"], "");
+        assert_eq!(imported.warnings.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_pg_service() {
+        let content = r#"
+[billing]
+host=billing.db
+port=5433
+dbname=billing
+user=billing_ro
+sslmode=require
+connect_timeout=5
+"#;
+        let mut imported = Imported::default();
+        parse_pg_service(content, &ImportOptions::default(), &mut imported);
+
+        assert_eq!(imported.databases.len(), 1);
+        let billing = &imported.databases[0];
+        assert_eq!(billing.alias, "pg-billing");
+        let vars = billing.vars.as_ref().unwrap();
+        assert_eq!(vars["host"], "billing.db");
+        assert_eq!(vars["database"], "billing");
+        assert_eq!(vars["query"], "sslmode=require&connect_timeout=5");
+    }
+}
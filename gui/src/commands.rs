@@ -2,14 +2,23 @@ use dbhub_core::{config, Database, InitResult};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// A database as seen by the frontend, `get_connections` always sets `dsn`,
+/// rendering it from the template for databases configured with `vars` only.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct DatabaseDto {
     pub alias: String,
     pub db_type: String,
-    pub dsn: String,
+    #[serde(default)]
+    pub dsn: Option<String>,
+    #[serde(default)]
+    pub vars: Option<HashMap<String, String>>,
     pub env: String,
+    #[serde(default)]
     pub description: Option<String>,
+    #[serde(default)]
     pub annotations: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub script: Option<String>,
 }
 
 impl From<Database> for DatabaseDto {
@@ -18,9 +27,11 @@ impl From<Database> for DatabaseDto {
             alias: db.alias,
             db_type: db.db_type,
             dsn: db.dsn,
+            vars: db.vars,
             env: db.env,
             description: db.description,
             annotations: db.annotations,
+            script: db.script,
         }
     }
 }
@@ -30,10 +41,12 @@ impl From<DatabaseDto> for Database {
         Self {
             db_type: dto.db_type,
             dsn: dto.dsn,
+            vars: dto.vars,
             env: dto.env,
             alias: dto.alias,
             description: dto.description,
             annotations: dto.annotations,
+            script: dto.script,
         }
     }
 }
@@ -62,8 +75,11 @@ pub async fn get_connections(
     );
     let mut grouped: HashMap<String, Vec<DatabaseDto>> = HashMap::new();
 
-    // Collect all databases first
-    let mut all_databases: Vec<Database> = config.databases;
+    // Collect all databases first, with the DSN of vars-only databases rendered
+    let mut all_databases: Vec<Database> = config.databases.clone();
+    for db in all_databases.iter_mut().filter(|db| db.dsn.is_none()) {
+        db.dsn = Some(config.dsn(db).unwrap_or_default());
+    }
 
     // Apply filters if provided
     if let Some(filter_params) = filter {
@@ -91,7 +107,7 @@ pub async fn get_connections(
                     db.db_type,
                     db.env,
                     db.description.as_ref().unwrap_or(&String::new()),
-                    db.dsn
+                    db.dsn.as_ref().unwrap_or(&String::new())
                 )
                 .to_lowercase();
 
@@ -276,7 +292,10 @@ pub async fn create_config_file(name: String) -> Result<String, String> {
     }
 
     // Create empty config file with basic structure
-    let default_content = "databases: ";
+    let default_content = format!(
+        "version: {}\ndatabases: ",
+        dbhub_core::config::CONFIG_VERSION
+    );
     std::fs::write(&file_path, default_content)
         .map_err(|e| format!("Failed to create config file: {e}"))?;
 
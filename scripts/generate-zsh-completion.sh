@@ -53,6 +53,38 @@ _dbhub() {
                     '--help[Print help]' \
                     && ret=0
                 ;;
+            (env)
+                _arguments "${_arguments_options[@]}" \
+                    '--shell=[Output syntax]:SHELL:(bash zsh fish dotenv)' \
+                    '-h[Print help]' \
+                    '--help[Print help]' \
+                    ':alias:_dbhub_aliases' \
+                    && ret=0
+                ;;
+            (run)
+                _arguments "${_arguments_options[@]}" \
+                    '-h[Print help]' \
+                    '--help[Print help]' \
+                    ':alias:_dbhub_aliases' \
+                    '*::command:_normal' \
+                    && ret=0
+                ;;
+            (script)
+                _arguments "${_arguments_options[@]}" \
+                    '-h[Print help]' \
+                    '--help[Print help]' \
+                    ':command:(list ls diff reset override test)' \
+                    '::db_type: ' \
+                    '--base[Write a script calling dbhub.base()]' \
+                    '*--vars=[Template variables]:KEY=VALUE: ' \
+                    '*--annotations=[Annotations]:KEY=VALUE: ' \
+                    '*--last-output=[Output lines of the previous step]:LINE: ' \
+                    '*--last-stderr=[Stderr lines of the previous step]:LINE: ' \
+                    '--last-exit-code=[Exit code of the previous step]:CODE: ' \
+                    '--state=[dbhub.state left by the previous step]:JSON: ' \
+                    '--count=[Step number]:COUNT: ' \
+                    && ret=0
+                ;;
             (completion|comp)
                 _arguments "${_arguments_options[@]}" \
                     '-h[Print help]' \
@@ -77,6 +109,12 @@ _dbhub_commands() {
         'c:Connect to a database using environment and database name'
         'context:Manage database connection contexts'
         'e:Manage database connection contexts'
+        'config:Manage configuration files'
+        'script:Manage the Lua scripts that build the connection commands'
+        'import:Import connections from other clients config files'
+        'export:Export connections to other tools formats'
+        'env:Print environment variable exports of a connection'
+        'run:Run a command with a connection environment variables'
         'completion:Generate shell completion scripts'
         'comp:Generate shell completion scripts'
         'help:Print this message or the help of the given subcommand(s)'